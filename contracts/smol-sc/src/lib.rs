@@ -7,7 +7,7 @@ use soroban_sdk::{
     crypto::Hash,
     token, vec,
     xdr::ToXdr,
-    Address, Bytes, BytesN, Env, String, Symbol, Val, Vec,
+    Address, Bytes, BytesN, Env, Map, String, Symbol, TryFromVal, Val, Vec,
 };
 
 #[contracterror]
@@ -25,6 +25,42 @@ pub enum Error {
     OfferDuplicate = 10,
     OfferNotFound = 11,
     NoRoyaltiesToClaim = 12,
+    AssetNotAllowed = 13,
+    AssetAmountTooLow = 14,
+    AssetReceivedShort = 15,
+    SaleAmountTooLow = 16,
+    RoyaltyRateOutOfRange = 17,
+    AuthorSplitInvalid = 18,
+    AuthorSplitImmutable = 19,
+    GlyphTitleTooLong = 20,
+    GlyphStoryTooLong = 21,
+    GlyphEmpty = 22,
+    GlyphWidthInvalid = 23,
+    GlyphNotRectangular = 24,
+    LegendTooLong = 25,
+    LegendDuplicate = 26,
+    LegendColorUnused = 27,
+    ColorNotInLegend = 28,
+    GlyphSymmetricDuplicate = 29,
+    GlyphMintSessionNotFound = 30,
+    GlyphTileInvalid = 31,
+    GlyphTilesMissing = 32,
+    GlyphHashMismatch = 33,
+    GlyphFramesInvalid = 34,
+    GlyphParentsInvalid = 35,
+    RecolorParentInvalid = 36,
+    CompositeLayersInvalid = 37,
+    CompositeLayerNotOwned = 38,
+    GlyphHasDependents = 39,
+    GlyphEditionsInvalid = 40,
+    GlyphEditionsSoldOut = 41,
+    GlyphOwnerIncorrect = 42,
+    ApprovalInsufficient = 43,
+    ApprovalExpirationInvalid = 44,
+    GlyphBaseUriTooLong = 45,
+    OfferLimitReached = 46,
+    GlyphLineageTooDeep = 47,
+    ColorRoyaltyMaxColorsInvalid = 48,
 }
 
 #[contracttype]
@@ -36,15 +72,51 @@ pub enum Storage {
     ColorClaimFee,
     ColorOwnerRoyaltyRate,
     GlyphMineFee,
+    GlyphMineColorFee,
+    GlyphSymmetryPolicy,
+    GlyphLineageRoyaltyRate,
+    GlyphLineageMaxDepth,
+    GlyphRecolorRoyaltyRate,
+    GlyphCompositeRoyaltyRate,
+    GlyphBurnFreesHash,
+    GlyphBaseUri,
     GlyphAuthorRoyaltyRate,
+    GlyphAuthorRoyaltyBounds,
     GlyphIndex,
-    ColorOwner(u32),                    // Color : Owner
-    Glyph(u32),                         // Glyph Index : Glyph
-    GlyphIndexHashMap(BytesN<32>),      // Glyph Hash : Glyph Index
-    GlyphOwner(u32),                    // Glyph Index : Owner
-    OfferSellGlyph(u32),                // Glyph Index : Vec<OfferBuy>
+    GlyphBalanceIndex,
+    ColorRoyaltyMaxColors,
+    ColorRoyaltyRemainderAddress,
+    AssetAllowlist,                                    // Map<SAC, Option<Min Amount>>
+    ColorOwner(u32),                                   // Color : Owner
+    Glyph(u32),                                        // Glyph Index : Glyph
+    GlyphPalette(u32),                                 // Glyph Index : GlyphPalette
+    GlyphMetadata(u32),                                // Glyph Index : GlyphMetadata
+    GlyphMintSession(u32),                             // Glyph Index : GlyphMintSession
+    GlyphTile(u32, u32),                               // Glyph Index, Tile : u8 colors
+    GlyphAnimation(u32),                               // Glyph Index : GlyphAnimation
+    GlyphLineage(u32),                                 // Glyph Index : GlyphLineage
+    GlyphChildren(u32),                                // Glyph Index : u32 children
+    GlyphChild(u32, u32),                              // Glyph Index, Child : Glyph Index
+    GlyphDependents(u32),          // Glyph Index : u32 glyphs reading its pixels
+    GlyphEditions(u32),            // Glyph Index : u32 editions minted after the first
+    GlyphIndexHashMap(BytesN<32>), // Glyph Hash : Glyph Index
+    GlyphHash(u32),                // Glyph Index : Glyph Hash
+    GlyphOwner(u32),               // Glyph Index : Owner
+    GlyphBalance(Address),         // Owner : Glyph count
+    GlyphApproval(u32),            // Glyph Index : (Approved, Live Until)
+    ColorApproval(u32),            // Color : (Approved, Live Until)
+    Operator(Address, Address),    // Owner, Operator : Live Until Ledger
+    GlyphAuthorSplit(u32),         // Glyph Index : RoyaltySplit
+    OfferSellGlyph(u32),           // Glyph Index : Vec<OfferBuy>
+    OfferSellGlyphSpender(u32),    // Glyph Index : Map<OfferBuy, Spender> listed by a spender
+    OfferBuyGlyph(u32),            // Glyph Index : Vec<Glyph Index> offering a swap for it
     OfferSellAsset(u32, Address, i128), // Glyph Index, SAC, Amount : Vec<Owner>
-    Royalties(Address, Address),        // Owner, SAC : Amount
+    OfferSellAssetEscrow(u32, Address, i128, Address), // Glyph Index, SAC, Amount, Owner : Received Amount
+    OfferSellAssetBids(u32), // Glyph Index : Vec<OfferSellAsset> escrowed bids for it
+    Royalties(Address, Address), // Owner, SAC : Amount
+    RoyaltiesAssets(Address), // Owner : Vec<SAC> with Royalties
+    GlyphRoyalties(u32, Address), // Glyph Index, SAC : Vec<(Color, Cumulative Royalties)>
+    ColorRoyaltiesCollected(u32, u32, Address), // Glyph Index, Color, SAC : ColorRoyalties
 }
 
 #[contracttype]
@@ -72,10 +144,195 @@ pub struct Glyph {
     pub colors: Bytes,    // u8 colors (up to 256 unique colors)
     pub legend: Vec<u32>, // map of u8 color to u32 color
     pub width: u32,
+    pub author_royalty_rate: i128, // royalty rates snapshotted at mint
+    pub color_owner_royalty_rate: i128,
+    pub encoding: GlyphEncoding, // how colors is stored, glyph_get returns Raw unless Tiled
+    pub edition: (u32, u32),     // (edition number, max supply), (1, 1) for a one of a kind
+}
+
+// glyphs minted before rates, encodings and editions were stored, see get_glyph
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+struct GlyphV1 {
+    author: Address,
+    colors: Bytes,
+    legend: Vec<u32>,
+    width: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum GlyphEncoding {
+    Raw,                 // one u8 color per pixel
+    Packed(u32, u32),    // Bits per pixel, Pixels : colors packed most significant bits first
+    RunLength,           // (u8 run length, u8 color) pairs
+    Tiled(u32),          // Tiles : colors live in GlyphTile entries, see glyph_tile_get
+    Delta,               // (u16 pixel, u8 color) triples changed from the previous frame
+    Recolor(u32),        // Glyph Index : colors are reused from this glyph with a new legend
+    Composite(u32, u32), // Width, Height : (glyph index, x, y, z) u32 layers rendered on read
+    Edition(u32),        // Glyph Index : colors are reused from this glyph's first edition
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum GlyphMintKind {
+    Raw(Bytes, Vec<u32>),                           // Colors, Legend
+    Recolor(u32, Vec<u32>),                         // Parent Glyph Index, Legend
+    Composite(Vec<(u32, u32, u32, u32)>, u32, u32), // Layers, Width, Height
+    Animated(Vec<Bytes>, Vec<u32>, u32),            // Frames, Legend, Width
+    Tiled(u32),   // Glyph Index : a session with its tiles uploaded
+    Edition(u32), // Glyph Index : the first edition
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlyphFrame {
+    pub colors: Bytes,
+    pub encoding: GlyphEncoding,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlyphAnimation {
+    pub durations: Vec<u32>, // milliseconds per frame, the first frame included
+    pub frames: Vec<GlyphFrame>, // every frame after the first, which is the glyph's colors
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlyphMintSession {
+    pub source: Address,
+    pub author: Address,
+    pub owner: Address,
+    pub legend: Vec<u32>,
+    pub width: u32,
+    pub height: u32,
+    pub glyph_hash: BytesN<32>, // checked against the uploaded tiles on finalize
+    pub metadata: GlyphMetadata,
+    pub author_royalty_rate: i128,
+    pub color_owner_royalty_rate: i128,
+    pub counts: Vec<u32>, // pixel count per legend color across the uploaded tiles
+    pub tiles_uploaded: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlyphLineage {
+    pub parents: Vec<(u32, Address)>, // (glyph index, author) of each parent
+    pub royalty_rate: i128,           // share of the author amount routed to the parents
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlyphMetadata {
+    pub title: String,
+    pub story: String,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GlyphMintOptions {
+    pub author_split: Option<Vec<(Address, u32)>>, // split recipients, the author alone when None
+    pub author_split_immutable: bool,              // locks the split once set
+    pub author_royalty_rate: Option<i128>,         // the default rate when None
+    pub parents: Option<Vec<u32>>,                 // glyph indexes this glyph derives from
+    pub max_supply: Option<u32>,                   // editions the author can mint, 1 when None
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlyphMintQuote {
+    pub fee_sac: Address,
+    pub mine_fee: i128,
+    pub color_fee: i128, // what the minter is charged, unclaimed colors are free
+    pub color_fees: Vec<(u32, u32, i128)>, // (u32 color, pixel count, amount) per royalty color
+    pub remainder_fee: i128, // share of the colors beyond the royalty cap
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SaleQuote {
+    pub author_amounts: Vec<(Address, i128)>, // author, split recipients and ancestor authors
+    pub color_amounts: Vec<(u32, u32, Option<Address>, i128)>, // (color, pixels, owner, amount)
+    pub color_owner_amount: i128,
+    pub remainder_address: Option<Address>, // share of the colors beyond the royalty cap
+    pub remainder_fee: i128,
+    pub seller_amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RoyaltySplit {
+    pub recipients: Vec<(Address, u32)>, // (recipient, basis points) summing to 10_000
+    pub immutable: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlyphPalette {
+    pub counts: Vec<(u32, u32)>, // (u32 color, pixel count) of the colors earning royalties
+    pub pixels: u32,             // visible pixels, transparent ones earn nothing
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorRoyalties {
+    pub owner: Address,  // the color owner accruing since collected
+    pub collected: i128, // the glyph's cumulative color royalties already paid out
 }
 
 mod tests;
 
+const MAX_GLYPH_WIDTH: u32 = 45;
+const MAX_GLYPH_FRAMES: u32 = 16;
+const MAX_GLYPH_PARENTS: u32 = 4;
+const MAX_GLYPH_LAYERS: u32 = 16;
+
+// every lineage level multiplies the ancestors a sale reads, so the ancestors paid are capped
+const MAX_GLYPH_LINEAGE_DEPTH: u32 = 3;
+const MAX_SALE_PAID_ANCESTORS: u32 = 4;
+
+// a sale writes each author side recipient, each royalty color, the remainder address and the
+// seller, together they're kept within a transaction's ledger entry limits. The glyph's own
+// split always fits, ancestors share what's left.
+const MAX_AUTHOR_SPLIT_RECIPIENTS: u32 = 4;
+const MAX_SALE_AUTHOR_RECIPIENTS: u32 = 8;
+
+// burning a glyph clears the swap offers asking for it and refunds its escrowed bids, a refund
+// touches several more ledger entries than a swap offer and together they fit one transaction
+const MAX_GLYPH_SWAP_OFFERS: u32 = 12;
+const MAX_GLYPH_BIDS: u32 = 4;
+
+// each paid color owner is a storage write when a sale settles, keep sales within ledger limits
+const DEFAULT_COLOR_ROYALTY_MAX_COLORS: u32 = 8;
+
+// the original author's cut of a recolor's author amount, until the admin sets another
+const DEFAULT_GLYPH_RECOLOR_ROYALTY_RATE: i128 = 10;
+
+// the layer authors' cut of a composite's author amount, until the admin sets another
+const DEFAULT_GLYPH_COMPOSITE_ROYALTY_RATE: i128 = 20;
+const MAX_COLOR_ROYALTY_MAX_COLORS: u32 = 8;
+
+// colors are 24-bit rgb
+const MAX_COLOR: u32 = 0xFFFFFF;
+
+// the one legend color above MAX_COLOR, it can't be claimed and earns no royalties
+const TRANSPARENT_COLOR: u32 = 0x1000000;
+const MAX_GLYPH_TILED_WIDTH: u32 = 256;
+const GLYPH_TILE_LENGTH: u32 = 4096;
+
+// GlyphSymmetryPolicy flags for the transforms checked against existing glyphs on mint
+const SYMMETRY_FLIP_HORIZONTAL: u32 = 1 << 0;
+const SYMMETRY_FLIP_VERTICAL: u32 = 1 << 1;
+const SYMMETRY_ROTATE: u32 = 1 << 2; // 90, 180 and 270 degrees
+const MAX_GLYPH_TITLE_LENGTH: u32 = 64;
+const MAX_GLYPH_STORY_LENGTH: u32 = 1024;
+
+// the standard NFT interface, token ids are glyph indexes
+const NFT_NAME: &str = "Smol Glyphs";
+const NFT_SYMBOL: &str = "GLYPH";
+const MAX_GLYPH_BASE_URI_LENGTH: u32 = 200;
+
 // TODO
 // add ttl extensions?
 // break up contract into separate files and helpers
@@ -83,6 +340,7 @@ mod tests;
 
 #[contractimpl]
 impl Contract {
+    #[allow(clippy::too_many_arguments)]
     pub fn __constructor(
         env: Env,
         admin: Address,
@@ -97,6 +355,8 @@ impl Contract {
             return Err(Error::AlreadyInitialized);
         }
 
+        check_royalty_rates(color_owner_royalty_rate, glyph_author_royalty_rate)?;
+
         env.storage()
             .instance()
             .set::<Storage, Address>(&Storage::Admin, &admin);
@@ -119,8 +379,18 @@ impl Contract {
             .instance()
             .set::<Storage, i128>(&Storage::GlyphAuthorRoyaltyRate, &glyph_author_royalty_rate);
 
+        // the fee asset is always accepted for offers out of the gate
+        let mut asset_allowlist = Map::<Address, Option<i128>>::new(&env);
+
+        asset_allowlist.set(fee_sac, None);
+
+        env.storage()
+            .instance()
+            .set::<Storage, Map<Address, Option<i128>>>(&Storage::AssetAllowlist, &asset_allowlist);
+
         Ok(())
     }
+    #[allow(clippy::too_many_arguments)]
     pub fn update(
         env: Env,
         admin: Option<Address>,
@@ -139,6 +409,32 @@ impl Contract {
 
         current_admin.require_auth();
 
+        let color_owner_royalty_rate_next = color_owner_royalty_rate.unwrap_or(
+            env.storage()
+                .instance()
+                .get::<Storage, i128>(&Storage::ColorOwnerRoyaltyRate)
+                .ok_or(Error::NotInitialized)?,
+        );
+
+        check_royalty_rates(
+            color_owner_royalty_rate_next,
+            glyph_author_royalty_rate.unwrap_or(
+                env.storage()
+                    .instance()
+                    .get::<Storage, i128>(&Storage::GlyphAuthorRoyaltyRate)
+                    .ok_or(Error::NotInitialized)?,
+            ),
+        )?;
+
+        // authors can still pick up to the bounds' max so that has to fit too
+        if let Some((_min, max)) = env
+            .storage()
+            .instance()
+            .get::<Storage, (i128, i128)>(&Storage::GlyphAuthorRoyaltyBounds)
+        {
+            check_royalty_rates(color_owner_royalty_rate_next, max)?;
+        }
+
         if let Some(admin) = admin {
             env.storage()
                 .instance()
@@ -148,6 +444,23 @@ impl Contract {
             env.storage()
                 .instance()
                 .set::<Storage, Address>(&Storage::FeeSAC, &fee_sac);
+
+            // the new fee asset has to be accepted for offers too, keeping any existing min amount
+            let mut asset_allowlist = get_asset_allowlist(&env);
+
+            if !asset_allowlist.contains_key(fee_sac.clone()) {
+                asset_allowlist.set(fee_sac.clone(), None);
+
+                env.storage()
+                    .instance()
+                    .set::<Storage, Map<Address, Option<i128>>>(
+                        &Storage::AssetAllowlist,
+                        &asset_allowlist,
+                    );
+
+                env.events()
+                    .publish((Symbol::new(&env, "asset_allow"), fee_sac), None::<i128>);
+            }
         }
         if let Some(fee_address) = fee_address {
             env.storage()
@@ -191,797 +504,4701 @@ impl Contract {
         Ok(())
     }
 
-    pub fn color_claim(env: Env, source: Address, owner: Address, color: u32) -> Result<(), Error> {
-        let color = if color > 0xFFFFFF {
-            return Err(Error::ColorOutOfRange);
-        } else {
-            color
-        };
-        let color_owner_key = Storage::ColorOwner(color);
+    pub fn asset_allow(env: Env, sac: Address, min_amount: Option<i128>) -> Result<(), Error> {
+        let admin = env
+            .storage()
+            .instance()
+            .get::<Storage, Address>(&Storage::Admin)
+            .ok_or(Error::NotInitialized)?;
 
-        if env.storage().persistent().has(&color_owner_key) {
-            return Err(Error::ColorAlreadyClaimed);
-        }
+        admin.require_auth();
 
-        env.storage().persistent().set(&color_owner_key, &owner);
+        let mut asset_allowlist = get_asset_allowlist(&env);
 
-        if let Err(err) = pay_fee(&env, Storage::ColorClaimFee, &source) {
-            return Err(err);
-        }
+        asset_allowlist.set(sac.clone(), min_amount);
+
+        env.storage()
+            .instance()
+            .set::<Storage, Map<Address, Option<i128>>>(&Storage::AssetAllowlist, &asset_allowlist);
 
         env.events()
-            .publish((Symbol::new(&env, "color_claim"), owner), color);
+            .publish((Symbol::new(&env, "asset_allow"), sac), min_amount);
 
         Ok(())
     }
-    pub fn color_owner_get(env: Env, color: u32) -> Result<Address, Error> {
-        env.storage()
-            .persistent()
-            .get::<Storage, Address>(&Storage::ColorOwner(color))
-            .ok_or(Error::ColorNotClaimed)
-    }
-    pub fn color_owner_transfer(env: Env, color: u32, to: Address) -> Result<(), Error> {
-        let color_owner_key = Storage::ColorOwner(color);
-
-        let color_owner = env
+    pub fn asset_disallow(env: Env, sac: Address) -> Result<(), Error> {
+        let admin = env
             .storage()
-            .persistent()
-            .get::<Storage, Address>(&color_owner_key)
-            .ok_or(Error::ColorNotClaimed)?;
+            .instance()
+            .get::<Storage, Address>(&Storage::Admin)
+            .ok_or(Error::NotInitialized)?;
+
+        admin.require_auth();
+
+        let mut asset_allowlist = get_asset_allowlist(&env);
 
-        color_owner.require_auth();
+        if asset_allowlist.remove(sac.clone()).is_none() {
+            return Err(Error::AssetNotAllowed);
+        }
 
-        env.storage().persistent().set(&color_owner_key, &to);
+        env.storage()
+            .instance()
+            .set::<Storage, Map<Address, Option<i128>>>(&Storage::AssetAllowlist, &asset_allowlist);
 
         env.events()
-            .publish((Symbol::new(&env, "color_owner_transfer"), to), color);
+            .publish((Symbol::new(&env, "asset_disallow"), sac), ());
 
         Ok(())
     }
+    pub fn color_royalty_max_colors_set(env: Env, max_colors: u32) -> Result<(), Error> {
+        let admin = env
+            .storage()
+            .instance()
+            .get::<Storage, Address>(&Storage::Admin)
+            .ok_or(Error::NotInitialized)?;
 
-    pub fn glyph_mint(
-        env: Env,
-        source: Address,
-        author: Address,
-        owner: Address,
-        colors: Bytes,
-        legend: Vec<u32>,
-        width: u32,
-        title: String,
-        story: String,
-    ) -> Result<u32, Error> {
-        // NOTE: we do not check that the color indexes exist in the legend
-        // TODO right now minting is free, should we charge a fee? Maybe KALE? Maybe charge if you want to store in persistent storage?
+        admin.require_auth();
 
-        if colors.len() > 45 * 45 {
-            return Err(Error::GlyphTooBig);
+        if max_colors > MAX_COLOR_ROYALTY_MAX_COLORS {
+            return Err(Error::ColorRoyaltyMaxColorsInvalid);
         }
 
-        let mut colors_extended_with_width = colors.clone();
-        let legend_xdr = legend.clone().to_xdr(&env);
-
-        colors_extended_with_width.append(&legend_xdr);
-        colors_extended_with_width.extend_from_slice(&width.to_be_bytes());
+        env.storage()
+            .instance()
+            .set::<Storage, u32>(&Storage::ColorRoyaltyMaxColors, &max_colors);
 
-        // ensure legend is sorted to prevent different hashes for the same glyph
-        for (index, color) in legend.iter().enumerate() {
-            let index = index as u32;
+        env.events().publish(
+            (Symbol::new(&env, "color_royalty_max_colors_set"),),
+            max_colors,
+        );
 
-            if index > 0 && color < legend.get_unchecked(index - 1) {
-                return Err(Error::LegendUnordered);
-            }
-        }
+        Ok(())
+    }
+    pub fn color_royalty_remainder_set(env: Env, remainder_address: Address) -> Result<(), Error> {
+        let admin = env
+            .storage()
+            .instance()
+            .get::<Storage, Address>(&Storage::Admin)
+            .ok_or(Error::NotInitialized)?;
 
-        // ensure the hash includes colors, legend and width
-        let glyph_hash = env.crypto().sha256(&colors_extended_with_width).to_bytes();
-        let glyph_hash_key = Storage::GlyphIndexHashMap(glyph_hash.clone());
+        admin.require_auth();
 
-        if env.storage().persistent().has::<Storage>(&glyph_hash_key) {
-            return Err(Error::GlyphAlreadyMinted);
-        }
+        env.storage()
+            .instance()
+            .set::<Storage, Address>(&Storage::ColorRoyaltyRemainderAddress, &remainder_address);
 
-        let glyph = Glyph {
-            author: author.clone(),
-            colors,
-            legend,
-            width,
-        };
+        env.events().publish(
+            (Symbol::new(&env, "color_royalty_remainder_set"),),
+            remainder_address,
+        );
 
-        let glyph_index = env
+        Ok(())
+    }
+    pub fn glyph_author_royalty_bounds_set(env: Env, min: i128, max: i128) -> Result<(), Error> {
+        let admin = env
             .storage()
             .instance()
-            .get::<Storage, u32>(&Storage::GlyphIndex)
-            .unwrap_or(0)
-            + 1;
+            .get::<Storage, Address>(&Storage::Admin)
+            .ok_or(Error::NotInitialized)?;
 
-        env.storage()
-            .instance()
-            .set::<Storage, u32>(&Storage::GlyphIndex, &glyph_index);
+        admin.require_auth();
 
-        env.storage()
-            .persistent()
-            .set::<Storage, u32>(&glyph_hash_key, &glyph_index);
+        if min < 0 || min > max {
+            return Err(Error::RoyaltyRateOutOfRange);
+        }
 
-        env.storage()
-            .persistent()
-            .set::<Storage, Glyph>(&Storage::Glyph(glyph_index), &glyph);
+        check_royalty_rates(
+            env.storage()
+                .instance()
+                .get::<Storage, i128>(&Storage::ColorOwnerRoyaltyRate)
+                .ok_or(Error::NotInitialized)?,
+            max,
+        )?;
 
         env.storage()
-            .persistent()
-            .set::<Storage, Address>(&Storage::GlyphOwner(glyph_index), &owner);
-
-        if let Err(err) = pay_fee(&env, Storage::GlyphMineFee, &source) {
-            return Err(err);
-        }
+            .instance()
+            .set::<Storage, (i128, i128)>(&Storage::GlyphAuthorRoyaltyBounds, &(min, max));
 
         env.events().publish(
-            (Symbol::new(&env, "glyph_mint"), author, owner),
-            (glyph_index, title, story),
+            (Symbol::new(&env, "glyph_author_royalty_bounds_set"),),
+            (min, max),
         );
 
-        Ok(glyph_index)
-    }
-    pub fn glyph_get(env: Env, glyph_index: u32) -> Result<Glyph, Error> {
-        env.storage()
-            .persistent()
-            .get::<Storage, Glyph>(&Storage::Glyph(glyph_index))
-            .ok_or(Error::GlyphNotMinted)
-    }
-    pub fn glyph_owner_get(env: Env, glyph_index: u32) -> Result<Address, Error> {
-        env.storage()
-            .persistent()
-            .get::<Storage, Address>(&Storage::GlyphOwner(glyph_index))
-            .ok_or(Error::GlyphNotMinted)
+        Ok(())
     }
-    pub fn glyph_owner_transfer(env: Env, glyph_index: u32, to: Address) -> Result<(), Error> {
-        let glyph_owner_key = Storage::GlyphOwner(glyph_index);
-
-        let glyph_owner = env
+    pub fn glyph_mine_color_fee_set(env: Env, color_fee: i128) -> Result<(), Error> {
+        let admin = env
             .storage()
-            .persistent()
-            .get::<Storage, Address>(&glyph_owner_key)
-            .ok_or(Error::GlyphNotMinted)?;
+            .instance()
+            .get::<Storage, Address>(&Storage::Admin)
+            .ok_or(Error::NotInitialized)?;
 
-        glyph_owner.require_auth();
+        admin.require_auth();
 
-        env.storage().persistent().set(&glyph_owner_key, &to);
+        env.storage()
+            .instance()
+            .set::<Storage, i128>(&Storage::GlyphMineColorFee, &color_fee);
 
         env.events()
-            .publish((Symbol::new(&env, "glyph_owner_transfer"), to), glyph_index);
+            .publish((Symbol::new(&env, "glyph_mine_color_fee_set"),), color_fee);
 
         Ok(())
     }
+    pub fn glyph_symmetry_policy_set(env: Env, policy: u32) -> Result<(), Error> {
+        let admin = env
+            .storage()
+            .instance()
+            .get::<Storage, Address>(&Storage::Admin)
+            .ok_or(Error::NotInitialized)?;
 
-    pub fn offer_sell_glyph(env: Env, sell: u32, buy: OfferBuy) -> Result<Option<Address>, Error> {
-        let glyph_owner_key = Storage::GlyphOwner(sell);
-        let offer_sell_glyph_key = Storage::OfferSellGlyph(sell);
+        admin.require_auth();
 
-        let glyph_owner = env
+        env.storage()
+            .instance()
+            .set::<Storage, u32>(&Storage::GlyphSymmetryPolicy, &policy);
+
+        env.events()
+            .publish((Symbol::new(&env, "glyph_symmetry_policy_set"),), policy);
+
+        Ok(())
+    }
+    pub fn glyph_lineage_set(env: Env, royalty_rate: i128, max_depth: u32) -> Result<(), Error> {
+        let admin = env
             .storage()
-            .persistent()
-            .get::<Storage, Address>(&glyph_owner_key)
-            .ok_or(Error::GlyphNotMinted)?;
+            .instance()
+            .get::<Storage, Address>(&Storage::Admin)
+            .ok_or(Error::NotInitialized)?;
 
-        glyph_owner.require_auth();
+        admin.require_auth();
 
-        match &buy {
-            OfferBuy::Glyph(buy) => {
-                let offer_buy_glyph_key = Storage::OfferSellGlyph(*buy);
+        if !(0..=100).contains(&royalty_rate) {
+            return Err(Error::RoyaltyRateOutOfRange);
+        }
 
-                let offers = env
-                    .storage()
+        if max_depth > MAX_GLYPH_LINEAGE_DEPTH {
+            return Err(Error::GlyphLineageTooDeep);
+        }
+
+        env.storage()
+            .instance()
+            .set::<Storage, i128>(&Storage::GlyphLineageRoyaltyRate, &royalty_rate);
+        env.storage()
+            .instance()
+            .set::<Storage, u32>(&Storage::GlyphLineageMaxDepth, &max_depth);
+
+        env.events().publish(
+            (Symbol::new(&env, "glyph_lineage_set"),),
+            (royalty_rate, max_depth),
+        );
+
+        Ok(())
+    }
+    pub fn glyph_recolor_royalty_rate_set(env: Env, royalty_rate: i128) -> Result<(), Error> {
+        let admin = env
+            .storage()
+            .instance()
+            .get::<Storage, Address>(&Storage::Admin)
+            .ok_or(Error::NotInitialized)?;
+
+        admin.require_auth();
+
+        if !(0..=100).contains(&royalty_rate) {
+            return Err(Error::RoyaltyRateOutOfRange);
+        }
+
+        env.storage()
+            .instance()
+            .set::<Storage, i128>(&Storage::GlyphRecolorRoyaltyRate, &royalty_rate);
+
+        env.events().publish(
+            (Symbol::new(&env, "glyph_recolor_royalty_rate_set"),),
+            royalty_rate,
+        );
+
+        Ok(())
+    }
+    pub fn glyph_composite_royalty_rate_set(env: Env, royalty_rate: i128) -> Result<(), Error> {
+        let admin = env
+            .storage()
+            .instance()
+            .get::<Storage, Address>(&Storage::Admin)
+            .ok_or(Error::NotInitialized)?;
+
+        admin.require_auth();
+
+        if !(0..=100).contains(&royalty_rate) {
+            return Err(Error::RoyaltyRateOutOfRange);
+        }
+
+        env.storage()
+            .instance()
+            .set::<Storage, i128>(&Storage::GlyphCompositeRoyaltyRate, &royalty_rate);
+
+        env.events().publish(
+            (Symbol::new(&env, "glyph_composite_royalty_rate_set"),),
+            royalty_rate,
+        );
+
+        Ok(())
+    }
+    pub fn glyph_burn_frees_hash_set(env: Env, frees_hash: bool) -> Result<(), Error> {
+        let admin = env
+            .storage()
+            .instance()
+            .get::<Storage, Address>(&Storage::Admin)
+            .ok_or(Error::NotInitialized)?;
+
+        admin.require_auth();
+
+        env.storage()
+            .instance()
+            .set::<Storage, bool>(&Storage::GlyphBurnFreesHash, &frees_hash);
+
+        env.events().publish(
+            (Symbol::new(&env, "glyph_burn_frees_hash_set"),),
+            frees_hash,
+        );
+
+        Ok(())
+    }
+    pub fn glyph_base_uri_set(env: Env, base_uri: String) -> Result<(), Error> {
+        let admin = env
+            .storage()
+            .instance()
+            .get::<Storage, Address>(&Storage::Admin)
+            .ok_or(Error::NotInitialized)?;
+
+        admin.require_auth();
+
+        if base_uri.len() > MAX_GLYPH_BASE_URI_LENGTH {
+            return Err(Error::GlyphBaseUriTooLong);
+        }
+
+        env.storage()
+            .instance()
+            .set::<Storage, String>(&Storage::GlyphBaseUri, &base_uri);
+
+        env.events()
+            .publish((Symbol::new(&env, "glyph_base_uri_set"),), base_uri);
+
+        Ok(())
+    }
+    // count the owners of glyphs minted before balances were tracked, newest first,
+    // returns how many glyphs are left to count
+    pub fn glyph_balances_migrate(env: Env, limit: u32) -> Result<u32, Error> {
+        let admin = env
+            .storage()
+            .instance()
+            .get::<Storage, Address>(&Storage::Admin)
+            .ok_or(Error::NotInitialized)?;
+
+        admin.require_auth();
+
+        let mut glyph_balance_index = get_glyph_balance_index(&env);
+        let mut migrated = 0;
+
+        while glyph_balance_index > 1 && migrated < limit {
+            glyph_balance_index -= 1;
+            migrated += 1;
+
+            if let Some(glyph_owner) = env
+                .storage()
+                .persistent()
+                .get::<Storage, Address>(&Storage::GlyphOwner(glyph_balance_index))
+            {
+                let balance_key = Storage::GlyphBalance(glyph_owner);
+                let balance = env
+                    .storage()
                     .persistent()
-                    .get::<Storage, Vec<OfferBuy>>(&offer_buy_glyph_key)
-                    .unwrap_or(Vec::new(&env));
+                    .get::<Storage, u32>(&balance_key)
+                    .unwrap_or(0);
+
+                env.storage()
+                    .persistent()
+                    .set::<Storage, u32>(&balance_key, &(balance + 1));
+            }
+        }
+
+        // counted glyphs are tracked as they change hands from here on
+        env.storage()
+            .instance()
+            .set::<Storage, u32>(&Storage::GlyphBalanceIndex, &glyph_balance_index);
+
+        env.events().publish(
+            (Symbol::new(&env, "glyph_balances_migrate"),),
+            glyph_balance_index,
+        );
+
+        Ok(glyph_balance_index - 1)
+    }
+    pub fn assets_get(env: Env) -> Result<Map<Address, Option<i128>>, Error> {
+        Ok(get_asset_allowlist(&env))
+    }
+
+    pub fn color_claim(env: Env, source: Address, owner: Address, color: u32) -> Result<(), Error> {
+        let color = if color > MAX_COLOR {
+            return Err(Error::ColorOutOfRange);
+        } else {
+            color
+        };
+        let color_owner_key = Storage::ColorOwner(color);
+
+        if env.storage().persistent().has(&color_owner_key) {
+            return Err(Error::ColorAlreadyClaimed);
+        }
+
+        env.storage().persistent().set(&color_owner_key, &owner);
+
+        if let Err(err) = pay_fee(&env, Storage::ColorClaimFee, &source) {
+            return Err(err);
+        }
+
+        env.events()
+            .publish((Symbol::new(&env, "color_claim"), owner), color);
+
+        Ok(())
+    }
+    pub fn color_owner_get(env: Env, color: u32) -> Result<Address, Error> {
+        env.storage()
+            .persistent()
+            .get::<Storage, Address>(&Storage::ColorOwner(color))
+            .ok_or(Error::ColorNotClaimed)
+    }
+    pub fn color_owner_transfer(env: Env, color: u32, to: Address) -> Result<(), Error> {
+        transfer_color(&env, &None, color, to)
+    }
+    pub fn color_owner_transfer_from(
+        env: Env,
+        spender: Address,
+        color: u32,
+        to: Address,
+    ) -> Result<(), Error> {
+        transfer_color(&env, &Some(spender), color, to)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn glyph_mint(
+        env: Env,
+        source: Address,
+        author: Address,
+        owner: Address,
+        colors: Bytes,
+        legend: Vec<u32>,
+        width: u32,
+        title: String,
+        story: String,
+        options: Option<GlyphMintOptions>,
+    ) -> Result<u32, Error> {
+        // TODO right now minting is free, should we charge a fee? Maybe KALE? Maybe charge if you want to store in persistent storage?
+
+        check_glyph(&colors, &legend, width)?;
+
+        let GlyphMintOptions {
+            author_split,
+            author_split_immutable,
+            author_royalty_rate,
+            parents,
+            max_supply,
+        } = options.unwrap_or_default();
+        let author_split = get_mint_author_split(author_split, author_split_immutable);
+
+        let glyph_lineage = match parents {
+            Some(parents) => Some(get_glyph_lineage(&env, &parents)?),
+            None => None,
+        };
+
+        if let Some(author_split) = &author_split {
+            check_author_split(author_split)?;
+        }
+
+        require_author_auth(&source, &author, &author_split, &author_royalty_rate);
+
+        // this mint is the first edition, the author mints the rest with glyph_mint_edition
+        let max_supply = max_supply.unwrap_or(1);
+
+        if max_supply == 0 {
+            return Err(Error::GlyphEditionsInvalid);
+        }
+
+        check_glyph_metadata(&title, &story)?;
+
+        let (author_royalty_rate, color_owner_royalty_rate) =
+            get_royalty_rates(&env, author_royalty_rate)?;
+
+        // check_glyph only lets canonical glyphs through so this is the one hash for this image
+        let glyph_hash = check_glyph_duplicate(&env, &colors, &legend, width)?;
+
+        // only the top colors by pixel count earn royalties, fixed at mint so sales stay cheap
+        let glyph_palette = get_glyph_palette(
+            &env,
+            get_palette(colors.clone(), &GlyphEncoding::Raw),
+            &legend,
+        )?;
+
+        // store the cheapest encoding, the hash above always covers the raw colors
+        let (colors, encoding) = encode_colors(&env, &colors, legend.len());
+
+        let glyph = Glyph {
+            author,
+            colors,
+            legend,
+            width,
+            author_royalty_rate,
+            color_owner_royalty_rate,
+            encoding,
+            edition: (1, max_supply),
+        };
+
+        let glyph_index = next_glyph_index(&env);
+
+        if let Some(glyph_lineage) = glyph_lineage {
+            store_glyph_lineage(&env, glyph_index, &glyph_lineage);
+        }
+
+        store_glyph(
+            &env,
+            &source,
+            GlyphMint {
+                owner,
+                glyph_index,
+                glyph,
+                glyph_hash,
+                glyph_palette,
+                author_split,
+                metadata: GlyphMetadata { title, story },
+            },
+        )?;
+
+        Ok(glyph_index)
+    }
+    #[allow(clippy::too_many_arguments)]
+    pub fn glyph_mint_recolor(
+        env: Env,
+        source: Address,
+        author: Address,
+        owner: Address,
+        parent: u32,
+        legend: Vec<u32>,
+        title: String,
+        story: String,
+        options: Option<GlyphMintOptions>,
+    ) -> Result<u32, Error> {
+        let (
+            parent,
+            Glyph {
+                author: parent_author,
+                colors: parent_colors,
+                legend: parent_legend,
+                width,
+                ..
+            },
+        ) = get_recolor_parent(&env, parent)?;
+
+        check_recolor_legend(&legend, &parent_legend)?;
+
+        // a recolor's lineage is the glyph it recolors
+        let GlyphMintOptions {
+            author_split,
+            author_split_immutable,
+            author_royalty_rate,
+            parents,
+            max_supply,
+        } = options.unwrap_or_default();
+        let author_split = get_mint_author_split(author_split, author_split_immutable);
+
+        if parents.is_some() {
+            return Err(Error::GlyphParentsInvalid);
+        }
+
+        // editions share the pixels this glyph reads through, as with a plain glyph
+        let max_supply = max_supply.unwrap_or(1);
+
+        if max_supply == 0 {
+            return Err(Error::GlyphEditionsInvalid);
+        }
+
+        if let Some(author_split) = &author_split {
+            check_author_split(author_split)?;
+        }
+
+        require_author_auth(&source, &author, &author_split, &author_royalty_rate);
+
+        check_glyph_metadata(&title, &story)?;
+
+        let (author_royalty_rate, color_owner_royalty_rate) =
+            get_royalty_rates(&env, author_royalty_rate)?;
+
+        // deduplicated as the image it renders, so it's caught against plain glyphs and
+        // recolors with the legend in another order
+        let (canonical_colors, canonical_legend) =
+            canonicalize_glyph(&env, &parent_colors, &legend)?;
+        let glyph_hash = check_glyph_duplicate(&env, &canonical_colors, &canonical_legend, width)?;
+
+        // the new legend's color owners earn by the parent's pixel counts
+        let glyph_palette = get_glyph_palette(
+            &env,
+            get_palette(parent_colors, &GlyphEncoding::Raw),
+            &legend,
+        )?;
+
+        let glyph = Glyph {
+            author,
+            colors: Bytes::new(&env),
+            legend,
+            width,
+            author_royalty_rate,
+            color_owner_royalty_rate,
+            encoding: GlyphEncoding::Recolor(parent),
+            edition: (1, max_supply),
+        };
+
+        let glyph_index = next_glyph_index(&env);
+
+        // the original author earns a recolor royalty through the lineage cascade
+        store_glyph_lineage(
+            &env,
+            glyph_index,
+            &GlyphLineage {
+                parents: vec![&env, (parent, parent_author)],
+                royalty_rate: env
+                    .storage()
+                    .instance()
+                    .get::<Storage, i128>(&Storage::GlyphRecolorRoyaltyRate)
+                    .unwrap_or(DEFAULT_GLYPH_RECOLOR_ROYALTY_RATE),
+            },
+        );
+        update_glyph_dependents(&env, parent, true);
+
+        store_glyph(
+            &env,
+            &source,
+            GlyphMint {
+                owner,
+                glyph_index,
+                glyph,
+                glyph_hash,
+                glyph_palette,
+                author_split,
+                metadata: GlyphMetadata { title, story },
+            },
+        )?;
+
+        Ok(glyph_index)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn glyph_mint_composite(
+        env: Env,
+        source: Address,
+        author: Address,
+        owner: Address,
+        layers: Vec<(u32, u32, u32, u32)>, // (glyph index, x, y, z) with higher z drawn on top
+        width: u32,
+        height: u32,
+        title: String,
+        story: String,
+        options: Option<GlyphMintOptions>,
+    ) -> Result<u32, Error> {
+        let draw_layers = get_composite_draw_layers(&env, &layers, width, height)?;
+        let mut glyph_lineage_parents = Vec::new(&env);
+
+        for (glyph_index, _, _, _) in draw_layers.iter() {
+            if glyph_lineage_parents
+                .iter()
+                .any(|(parent, _)| parent == glyph_index)
+            {
+                continue;
+            }
+
+            // each distinct layer glyph is a lineage parent, keep the royalty cascade bounded
+            if glyph_lineage_parents.len() == MAX_GLYPH_PARENTS {
+                return Err(Error::CompositeLayersInvalid);
+            }
+
+            let Glyph {
+                author: layer_author,
+                ..
+            } = get_glyph(&env, glyph_index).ok_or(Error::GlyphNotMinted)?;
+
+            let layer_owner = env
+                .storage()
+                .persistent()
+                .get::<Storage, Address>(&Storage::GlyphOwner(glyph_index))
+                .ok_or(Error::GlyphNotMinted)?;
+
+            // source authorizes the mint fee, and must own each layer, be approved for it or be
+            // its author, who keeps the right to reuse their work after selling it
+            if !is_glyph_approved(&env, &layer_owner, &source, glyph_index)
+                && layer_author != source
+            {
+                return Err(Error::CompositeLayerNotOwned);
+            }
+
+            glyph_lineage_parents.push_back((glyph_index, layer_author));
+        }
+
+        // a composite's lineage is its layers
+        let GlyphMintOptions {
+            author_split,
+            author_split_immutable,
+            author_royalty_rate,
+            parents,
+            max_supply,
+        } = options.unwrap_or_default();
+        let author_split = get_mint_author_split(author_split, author_split_immutable);
+
+        if parents.is_some() {
+            return Err(Error::GlyphParentsInvalid);
+        }
+
+        // editions share the pixels this glyph reads through, as with a plain glyph
+        let max_supply = max_supply.unwrap_or(1);
+
+        if max_supply == 0 {
+            return Err(Error::GlyphEditionsInvalid);
+        }
+
+        if let Some(author_split) = &author_split {
+            check_author_split(author_split)?;
+        }
+
+        require_author_auth(&source, &author, &author_split, &author_royalty_rate);
+
+        check_glyph_metadata(&title, &story)?;
+
+        let (author_royalty_rate, color_owner_royalty_rate) =
+            get_royalty_rates(&env, author_royalty_rate)?;
+
+        // hashed and counted as the flattened image, so it dedupes against plain glyphs too
+        let (colors, legend) = render_composite(&env, &draw_layers, width, height)?;
+
+        let glyph_hash = check_glyph_duplicate(&env, &colors, &legend, width)?;
+
+        let glyph_palette =
+            get_glyph_palette(&env, get_palette(colors, &GlyphEncoding::Raw), &legend)?;
+
+        let glyph = Glyph {
+            author,
+            colors: encode_layers(&env, &draw_layers),
+            legend,
+            width,
+            author_royalty_rate,
+            color_owner_royalty_rate,
+            encoding: GlyphEncoding::Composite(width, height),
+            edition: (1, max_supply),
+        };
+
+        let glyph_index = next_glyph_index(&env);
+
+        for (parent, _) in glyph_lineage_parents.iter() {
+            update_glyph_dependents(&env, parent, true);
+        }
+
+        // the layer authors earn through the lineage cascade when the composite sells
+        store_glyph_lineage(
+            &env,
+            glyph_index,
+            &GlyphLineage {
+                parents: glyph_lineage_parents,
+                royalty_rate: env
+                    .storage()
+                    .instance()
+                    .get::<Storage, i128>(&Storage::GlyphCompositeRoyaltyRate)
+                    .unwrap_or(DEFAULT_GLYPH_COMPOSITE_ROYALTY_RATE),
+            },
+        );
+
+        store_glyph(
+            &env,
+            &source,
+            GlyphMint {
+                owner,
+                glyph_index,
+                glyph,
+                glyph_hash,
+                glyph_palette,
+                author_split,
+                metadata: GlyphMetadata { title, story },
+            },
+        )?;
+
+        Ok(glyph_index)
+    }
+
+    pub fn glyph_mint_edition(env: Env, glyph_index: u32, owner: Address) -> Result<u32, Error> {
+        let glyph = get_glyph(&env, glyph_index).ok_or(Error::GlyphNotMinted)?;
+
+        // later editions are always minted from the first
+        let (1, max_supply) = glyph.edition else {
+            return Err(Error::GlyphEditionsInvalid);
+        };
+
+        // burned editions still count against the supply
+        let glyph_editions_key = Storage::GlyphEditions(glyph_index);
+        let glyph_editions = env
+            .storage()
+            .persistent()
+            .get::<Storage, u32>(&glyph_editions_key)
+            .unwrap_or(0);
+
+        let edition = glyph_editions + 2;
+
+        if edition > max_supply {
+            return Err(Error::GlyphEditionsSoldOut);
+        }
+
+        let glyph_palette = get_stored_glyph_palette(&env, glyph_index)?;
+        let metadata = env
+            .storage()
+            .persistent()
+            .get::<Storage, GlyphMetadata>(&Storage::GlyphMetadata(glyph_index))
+            .ok_or(Error::GlyphNotMinted)?;
+
+        // the pixels are stored once, every edition has its own owner, offers and royalties
+        let edition_glyph = Glyph {
+            colors: Bytes::new(&env),
+            encoding: GlyphEncoding::Edition(glyph_index),
+            edition: (edition, max_supply),
+            ..glyph
+        };
+
+        let edition_index = next_glyph_index(&env);
+
+        env.storage()
+            .persistent()
+            .set::<Storage, u32>(&glyph_editions_key, &(glyph_editions + 1));
+
+        update_glyph_dependents(&env, glyph_index, true);
+
+        env.storage()
+            .persistent()
+            .set::<Storage, Glyph>(&Storage::Glyph(edition_index), &edition_glyph);
+
+        env.storage()
+            .persistent()
+            .set::<Storage, GlyphPalette>(&Storage::GlyphPalette(edition_index), &glyph_palette);
+
+        set_glyph_owner(&env, edition_index, None, Some(&owner));
+
+        // only the author can add to the supply
+        pay_mine_fees(&env, &edition_glyph.author, edition_index)?;
+
+        env.events().publish(
+            (Symbol::new(&env, "glyph_mint"), edition_glyph.author, owner),
+            (
+                edition_index,
+                metadata.title,
+                metadata.story,
+                edition_glyph.edition,
+            ),
+        );
+
+        Ok(edition_index)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn glyph_mint_begin(
+        env: Env,
+        source: Address,
+        author: Address,
+        owner: Address,
+        legend: Vec<u32>,
+        width: u32,
+        height: u32,
+        glyph_hash: BytesN<32>,
+        title: String,
+        story: String,
+        options: Option<GlyphMintOptions>,
+    ) -> Result<u32, Error> {
+        source.require_auth();
+
+        if width == 0 || width > MAX_GLYPH_TILED_WIDTH {
+            return Err(Error::GlyphWidthInvalid);
+        }
+
+        if height == 0 || height > MAX_GLYPH_TILED_WIDTH {
+            return Err(Error::GlyphTooBig);
+        }
+
+        check_legend(&legend)?;
+
+        // the session carries only the pixels and royalty terms, tiles are read by this glyph's
+        // own index so they can't back editions
+        let GlyphMintOptions {
+            author_split,
+            author_split_immutable,
+            author_royalty_rate,
+            parents,
+            max_supply,
+        } = options.unwrap_or_default();
+        let author_split = get_mint_author_split(author_split, author_split_immutable);
+
+        if parents.is_some() {
+            return Err(Error::GlyphParentsInvalid);
+        }
+
+        if max_supply.is_some_and(|max_supply| max_supply != 1) {
+            return Err(Error::GlyphEditionsInvalid);
+        }
+
+        if let Some(author_split) = &author_split {
+            check_author_split(author_split)?;
+        }
+
+        require_author_auth(&source, &author, &author_split, &author_royalty_rate);
+
+        check_glyph_metadata(&title, &story)?;
+
+        let (author_royalty_rate, color_owner_royalty_rate) =
+            get_royalty_rates(&env, author_royalty_rate)?;
+
+        if env
+            .storage()
+            .persistent()
+            .has::<Storage>(&Storage::GlyphIndexHashMap(glyph_hash.clone()))
+        {
+            return Err(Error::GlyphAlreadyMinted);
+        }
+
+        // reserve the glyph index up front so tiles are uploaded straight to their final keys
+        let glyph_index = next_glyph_index(&env);
+
+        // the split needs no checks on finalize so it goes straight to its final key
+        if let Some(author_split) = author_split {
+            env.storage().persistent().set::<Storage, RoyaltySplit>(
+                &Storage::GlyphAuthorSplit(glyph_index),
+                &author_split,
+            );
+        }
+
+        let mut counts = Vec::new(&env);
+
+        for _ in 0..legend.len() {
+            counts.push_back(0);
+        }
+
+        env.storage().persistent().set::<Storage, GlyphMintSession>(
+            &Storage::GlyphMintSession(glyph_index),
+            &GlyphMintSession {
+                source,
+                author,
+                owner,
+                legend,
+                width,
+                height,
+                glyph_hash,
+                metadata: GlyphMetadata { title, story },
+                author_royalty_rate,
+                color_owner_royalty_rate,
+                counts,
+                tiles_uploaded: 0,
+            },
+        );
+
+        Ok(glyph_index)
+    }
+    pub fn glyph_mint_tile(
+        env: Env,
+        glyph_index: u32,
+        tile: u32,
+        colors: Bytes,
+    ) -> Result<(), Error> {
+        let session_key = Storage::GlyphMintSession(glyph_index);
+        let mut session = env
+            .storage()
+            .persistent()
+            .get::<Storage, GlyphMintSession>(&session_key)
+            .ok_or(Error::GlyphMintSessionNotFound)?;
+
+        session.source.require_auth();
+
+        let pixels = session.width * session.height;
+        let tiles = pixels.div_ceil(GLYPH_TILE_LENGTH);
+        let tile_key = Storage::GlyphTile(glyph_index, tile);
+
+        // every tile is full length except the last
+        if tile >= tiles
+            || colors.len() != GLYPH_TILE_LENGTH.min(pixels - tile * GLYPH_TILE_LENGTH)
+            || env.storage().persistent().has::<Storage>(&tile_key)
+        {
+            return Err(Error::GlyphTileInvalid);
+        }
+
+        let mut colors_bytes = [0u8; GLYPH_TILE_LENGTH as usize];
+
+        colors.copy_into_slice(&mut colors_bytes[..colors.len() as usize]);
+
+        let mut palette = [0u32; 256];
+
+        for color in colors_bytes[..colors.len() as usize].iter() {
+            palette[*color as usize] += 1;
+        }
+
+        for (index, count) in palette.into_iter().enumerate() {
+            if count == 0 {
+                continue;
+            }
+
+            if index >= session.legend.len() as usize {
+                return Err(Error::ColorNotInLegend);
+            }
+
+            let index = index as u32;
+
+            session
+                .counts
+                .set(index, session.counts.get_unchecked(index) + count);
+        }
+
+        session.tiles_uploaded += 1;
+
+        env.storage()
+            .persistent()
+            .set::<Storage, Bytes>(&tile_key, &colors);
+
+        env.storage()
+            .persistent()
+            .set::<Storage, GlyphMintSession>(&session_key, &session);
+
+        Ok(())
+    }
+    pub fn glyph_mint_finalize(env: Env, glyph_index: u32) -> Result<u32, Error> {
+        let session_key = Storage::GlyphMintSession(glyph_index);
+        let session = env
+            .storage()
+            .persistent()
+            .get::<Storage, GlyphMintSession>(&session_key)
+            .ok_or(Error::GlyphMintSessionNotFound)?;
+
+        // the source's auth is required when paying the mint fee
+        let palette = get_session_palette(&session)?;
+        let GlyphMintSession {
+            source,
+            author,
+            owner,
+            legend,
+            width,
+            height,
+            glyph_hash,
+            metadata,
+            author_royalty_rate,
+            color_owner_royalty_rate,
+            ..
+        } = session;
+        let tiles = (width * height).div_ceil(GLYPH_TILE_LENGTH);
+
+        // the same hash a single transaction mint would produce for these colors
+        let mut colors = Bytes::new(&env);
+
+        for tile in 0..tiles {
+            colors.append(
+                &env.storage()
+                    .persistent()
+                    .get::<Storage, Bytes>(&Storage::GlyphTile(glyph_index, tile))
+                    .ok_or(Error::GlyphTilesMissing)?,
+            );
+        }
+
+        if get_glyph_hash(&env, &colors, &legend, width) != glyph_hash {
+            return Err(Error::GlyphHashMismatch);
+        }
+
+        // the whole canvas goes through the same duplicate and symmetry checks as a single mint
+        check_glyph_duplicate(&env, &colors, &legend, width)?;
+
+        let glyph_palette = get_glyph_palette(&env, palette, &legend)?;
+
+        let glyph = Glyph {
+            author,
+            colors: Bytes::new(&env),
+            legend,
+            width,
+            author_royalty_rate,
+            color_owner_royalty_rate,
+            encoding: GlyphEncoding::Tiled(tiles),
+            edition: (1, 1),
+        };
+
+        env.storage().persistent().remove::<Storage>(&session_key);
+
+        store_glyph(
+            &env,
+            &source,
+            GlyphMint {
+                owner,
+                glyph_index,
+                glyph,
+                glyph_hash,
+                glyph_palette,
+                author_split: None,
+                metadata,
+            },
+        )?;
+
+        Ok(glyph_index)
+    }
+    pub fn glyph_mint_cancel(env: Env, glyph_index: u32) -> Result<(), Error> {
+        let session_key = Storage::GlyphMintSession(glyph_index);
+        let GlyphMintSession {
+            source,
+            width,
+            height,
+            ..
+        } = env
+            .storage()
+            .persistent()
+            .get::<Storage, GlyphMintSession>(&session_key)
+            .ok_or(Error::GlyphMintSessionNotFound)?;
+
+        source.require_auth();
+
+        // the reserved glyph index is left unused
+        for tile in 0..(width * height).div_ceil(GLYPH_TILE_LENGTH) {
+            env.storage()
+                .persistent()
+                .remove::<Storage>(&Storage::GlyphTile(glyph_index, tile));
+        }
+
+        env.storage()
+            .persistent()
+            .remove::<Storage>(&Storage::GlyphAuthorSplit(glyph_index));
+
+        env.storage().persistent().remove::<Storage>(&session_key);
+
+        Ok(())
+    }
+    #[allow(clippy::too_many_arguments)]
+    pub fn glyph_mint_animated(
+        env: Env,
+        source: Address,
+        author: Address,
+        owner: Address,
+        frames: Vec<Bytes>,
+        durations: Vec<u32>,
+        legend: Vec<u32>,
+        width: u32,
+        title: String,
+        story: String,
+        options: Option<GlyphMintOptions>,
+    ) -> Result<u32, Error> {
+        if durations.len() != frames.len() || durations.contains(0) {
+            return Err(Error::GlyphFramesInvalid);
+        }
+
+        let palette = get_animation_palette(&frames, &legend, width)?;
+        let first_frame = frames.get_unchecked(0);
+
+        // frames are read by this glyph's own index so they can't back editions
+        let GlyphMintOptions {
+            author_split,
+            author_split_immutable,
+            author_royalty_rate,
+            parents,
+            max_supply,
+        } = options.unwrap_or_default();
+        let author_split = get_mint_author_split(author_split, author_split_immutable);
+
+        let glyph_lineage = match parents {
+            Some(parents) => Some(get_glyph_lineage(&env, &parents)?),
+            None => None,
+        };
+
+        if max_supply.is_some_and(|max_supply| max_supply != 1) {
+            return Err(Error::GlyphEditionsInvalid);
+        }
+
+        if let Some(author_split) = &author_split {
+            check_author_split(author_split)?;
+        }
+
+        require_author_auth(&source, &author, &author_split, &author_royalty_rate);
+
+        check_glyph_metadata(&title, &story)?;
+
+        let (author_royalty_rate, color_owner_royalty_rate) =
+            get_royalty_rates(&env, author_royalty_rate)?;
+
+        let glyph_hash = check_animation_duplicate(&env, &frames, &legend, width)?;
+        let glyph_palette = get_glyph_palette(&env, palette, &legend)?;
+
+        // each later frame is stored whole or as a delta, whichever is smaller
+        let mut animation_frames = Vec::new(&env);
+
+        for index in 1..frames.len() {
+            let frame = frames.get_unchecked(index);
+            let (frame_colors, frame_encoding) = encode_colors(&env, &frame, legend.len());
+            let delta = encode_delta(&env, &frames.get_unchecked(index - 1), &frame);
+
+            animation_frames.push_back(if delta.len() < frame_colors.len() {
+                GlyphFrame {
+                    colors: delta,
+                    encoding: GlyphEncoding::Delta,
+                }
+            } else {
+                GlyphFrame {
+                    colors: frame_colors,
+                    encoding: frame_encoding,
+                }
+            });
+        }
+
+        let (colors, encoding) = encode_colors(&env, &first_frame, legend.len());
+
+        let glyph = Glyph {
+            author,
+            colors,
+            legend,
+            width,
+            author_royalty_rate,
+            color_owner_royalty_rate,
+            encoding,
+            edition: (1, 1),
+        };
+
+        let glyph_index = next_glyph_index(&env);
+
+        if let Some(glyph_lineage) = glyph_lineage {
+            store_glyph_lineage(&env, glyph_index, &glyph_lineage);
+        }
+
+        env.storage().persistent().set::<Storage, GlyphAnimation>(
+            &Storage::GlyphAnimation(glyph_index),
+            &GlyphAnimation {
+                durations,
+                frames: animation_frames,
+            },
+        );
+
+        store_glyph(
+            &env,
+            &source,
+            GlyphMint {
+                owner,
+                glyph_index,
+                glyph,
+                glyph_hash,
+                glyph_palette,
+                author_split,
+                metadata: GlyphMetadata { title, story },
+            },
+        )?;
+
+        Ok(glyph_index)
+    }
+    pub fn glyph_mint_quote(env: Env, kind: GlyphMintKind) -> Result<GlyphMintQuote, Error> {
+        let fee_sac = env
+            .storage()
+            .instance()
+            .get::<Storage, Address>(&Storage::FeeSAC)
+            .ok_or(Error::NotInitialized)?;
+        let mine_fee = env
+            .storage()
+            .instance()
+            .get::<Storage, i128>(&Storage::GlyphMineFee)
+            .ok_or(Error::NotInitialized)?;
+        let color_fee = env
+            .storage()
+            .instance()
+            .get::<Storage, i128>(&Storage::GlyphMineColorFee)
+            .unwrap_or(0);
+
+        // the same palette each mint entry point stores and charges the color fee by
+        let glyph_palette = match kind {
+            GlyphMintKind::Raw(colors, legend) => {
+                get_glyph_palette(&env, get_palette(colors, &GlyphEncoding::Raw), &legend)?
+            }
+            GlyphMintKind::Recolor(parent, legend) => {
+                let (
+                    _,
+                    Glyph {
+                        colors: parent_colors,
+                        legend: parent_legend,
+                        ..
+                    },
+                ) = get_recolor_parent(&env, parent)?;
+
+                check_recolor_legend(&legend, &parent_legend)?;
+
+                get_glyph_palette(
+                    &env,
+                    get_palette(parent_colors, &GlyphEncoding::Raw),
+                    &legend,
+                )?
+            }
+            GlyphMintKind::Composite(layers, width, height) => {
+                let draw_layers = get_composite_draw_layers(&env, &layers, width, height)?;
+                let (colors, legend) = render_composite(&env, &draw_layers, width, height)?;
+
+                get_glyph_palette(&env, get_palette(colors, &GlyphEncoding::Raw), &legend)?
+            }
+            GlyphMintKind::Animated(frames, legend, width) => get_glyph_palette(
+                &env,
+                get_animation_palette(&frames, &legend, width)?,
+                &legend,
+            )?,
+            GlyphMintKind::Tiled(glyph_index) => {
+                let session = env
+                    .storage()
+                    .persistent()
+                    .get::<Storage, GlyphMintSession>(&Storage::GlyphMintSession(glyph_index))
+                    .ok_or(Error::GlyphMintSessionNotFound)?;
+
+                get_glyph_palette(&env, get_session_palette(&session)?, &session.legend)?
+            }
+            GlyphMintKind::Edition(glyph_index) => {
+                let Glyph { edition, .. } =
+                    get_glyph(&env, glyph_index).ok_or(Error::GlyphNotMinted)?;
+
+                // later editions are always minted from the first
+                if edition.0 != 1 {
+                    return Err(Error::GlyphEditionsInvalid);
+                }
+
+                get_stored_glyph_palette(&env, glyph_index)?
+            }
+        };
+        let ColorShares {
+            color_amounts,
+            remainder_fee,
+            total: color_fee,
+        } = get_color_shares(&env, &glyph_palette, &color_fee)?;
+        let mut color_fees = Vec::new(&env);
+
+        for (color, count, _color_owner, amount) in color_amounts.iter() {
+            color_fees.push_back((color, count, amount));
+        }
+
+        // matches what the remainder address is credited
+        let remainder_fee =
+            remainder_fee.map_or(0, |(_remainder_address, remainder_fee)| remainder_fee);
+
+        Ok(GlyphMintQuote {
+            fee_sac,
+            mine_fee,
+            color_fee,
+            color_fees,
+            remainder_fee,
+        })
+    }
+    pub fn glyph_hash_compute(
+        env: Env,
+        colors: Bytes,
+        legend: Vec<u32>,
+        width: u32,
+    ) -> Result<(BytesN<32>, Option<u32>), Error> {
+        let (colors, legend) = canonicalize_glyph(&env, &colors, &legend)?;
+
+        check_glyph(&colors, &legend, width)?;
+
+        let glyph_hash = get_glyph_hash(&env, &colors, &legend, width);
+        let glyph_index = env
+            .storage()
+            .persistent()
+            .get::<Storage, u32>(&Storage::GlyphIndexHashMap(glyph_hash.clone()));
+
+        Ok((glyph_hash, glyph_index))
+    }
+    pub fn glyph_get(env: Env, glyph_index: u32) -> Result<Glyph, Error> {
+        let mut glyph = get_glyph(&env, glyph_index).ok_or(Error::GlyphNotMinted)?;
+
+        // tiled glyphs are too big to return whole, see glyph_tile_get
+        if !matches!(glyph.encoding, GlyphEncoding::Tiled(_)) {
+            glyph.colors = decode_colors(&env, &glyph.colors, &glyph.encoding);
+            glyph.encoding = GlyphEncoding::Raw;
+        }
+
+        Ok(glyph)
+    }
+    pub fn glyph_lineage_get(env: Env, glyph_index: u32) -> Result<(Vec<u32>, u32), Error> {
+        if !env
+            .storage()
+            .persistent()
+            .has::<Storage>(&Storage::Glyph(glyph_index))
+        {
+            return Err(Error::GlyphNotMinted);
+        }
+
+        let max_depth = get_glyph_lineage_max_depth(&env);
+
+        // ancestors breadth first, as far up as royalties cascade
+        let mut ancestors = Vec::new(&env);
+        let mut level = vec![&env, glyph_index];
+
+        for _ in 0..max_depth {
+            let mut next_level = Vec::new(&env);
+
+            for glyph_index in level.iter() {
+                if let Some(GlyphLineage { parents, .. }) = env
+                    .storage()
+                    .persistent()
+                    .get::<Storage, GlyphLineage>(&Storage::GlyphLineage(glyph_index))
+                {
+                    for (parent, _) in parents.iter() {
+                        if !ancestors.contains(parent) {
+                            ancestors.push_back(parent);
+                            next_level.push_back(parent);
+                        }
+                    }
+                }
+            }
+
+            level = next_level;
+        }
+
+        // children can be unbounded, page through them with glyph_children_get
+        let children = env
+            .storage()
+            .persistent()
+            .get::<Storage, u32>(&Storage::GlyphChildren(glyph_index))
+            .unwrap_or(0);
+
+        Ok((ancestors, children))
+    }
+    pub fn glyph_children_get(
+        env: Env,
+        glyph_index: u32,
+        start: u32,
+        limit: u32,
+    ) -> Result<Vec<u32>, Error> {
+        if !env
+            .storage()
+            .persistent()
+            .has::<Storage>(&Storage::Glyph(glyph_index))
+        {
+            return Err(Error::GlyphNotMinted);
+        }
+
+        let children_length = env
+            .storage()
+            .persistent()
+            .get::<Storage, u32>(&Storage::GlyphChildren(glyph_index))
+            .unwrap_or(0);
+
+        let mut children = Vec::new(&env);
+
+        for child in start..children_length.min(start.saturating_add(limit)) {
+            children.push_back(
+                env.storage()
+                    .persistent()
+                    .get::<Storage, u32>(&Storage::GlyphChild(glyph_index, child))
+                    .ok_or(Error::GlyphNotMinted)?,
+            );
+        }
+
+        Ok(children)
+    }
+    pub fn glyph_frames_get(env: Env, glyph_index: u32) -> Result<Vec<(u32, Bytes)>, Error> {
+        let Glyph {
+            colors, encoding, ..
+        } = get_glyph(&env, glyph_index).ok_or(Error::GlyphNotMinted)?;
+
+        let mut frame = decode_colors(&env, &colors, &encoding);
+        let mut frames = Vec::new(&env);
+
+        // a still glyph is a single frame without a duration
+        let GlyphAnimation {
+            durations,
+            frames: animation_frames,
+        } = match env
+            .storage()
+            .persistent()
+            .get::<Storage, GlyphAnimation>(&Storage::GlyphAnimation(glyph_index))
+        {
+            Some(animation) => animation,
+            None => return Ok(vec![&env, (0, frame)]),
+        };
+
+        frames.push_back((durations.get_unchecked(0), frame.clone()));
+
+        for (index, GlyphFrame { colors, encoding }) in animation_frames.iter().enumerate() {
+            frame = match encoding {
+                GlyphEncoding::Delta => decode_delta(&env, &frame, &colors),
+                _ => decode_colors(&env, &colors, &encoding),
+            };
+
+            frames.push_back((durations.get_unchecked(index as u32 + 1), frame.clone()));
+        }
+
+        Ok(frames)
+    }
+    pub fn glyph_layers_get(
+        env: Env,
+        glyph_index: u32,
+    ) -> Result<Vec<(u32, u32, u32, u32)>, Error> {
+        let Glyph {
+            colors, encoding, ..
+        } = get_glyph(&env, glyph_index).ok_or(Error::GlyphNotMinted)?;
+
+        // in draw order, glyphs that aren't composites have no layers
+        match encoding {
+            GlyphEncoding::Composite(..) => Ok(decode_layers(&env, &colors)),
+            _ => Ok(Vec::new(&env)),
+        }
+    }
+    pub fn glyph_tile_get(
+        env: Env,
+        glyph_index: u32,
+        start: u32,
+        limit: u32,
+    ) -> Result<Vec<Bytes>, Error> {
+        let Glyph {
+            colors, encoding, ..
+        } = get_glyph(&env, glyph_index).ok_or(Error::GlyphNotMinted)?;
+
+        let mut tiles = Vec::new(&env);
+
+        match encoding {
+            GlyphEncoding::Tiled(tiles_length) => {
+                for tile in start..tiles_length.min(start.saturating_add(limit)) {
+                    tiles.push_back(
+                        env.storage()
+                            .persistent()
+                            .get::<Storage, Bytes>(&Storage::GlyphTile(glyph_index, tile))
+                            .ok_or(Error::GlyphNotMinted)?,
+                    );
+                }
+            }
+            // untiled glyphs are a single tile
+            _ => {
+                if start == 0 && limit > 0 {
+                    tiles.push_back(decode_colors(&env, &colors, &encoding));
+                }
+            }
+        }
+
+        Ok(tiles)
+    }
+    pub fn glyph_palette_get(env: Env, glyph_index: u32) -> Result<GlyphPalette, Error> {
+        get_stored_glyph_palette(&env, glyph_index)
+    }
+    pub fn glyph_author_split_get(
+        env: Env,
+        glyph_index: u32,
+    ) -> Result<Option<RoyaltySplit>, Error> {
+        let glyph_index = get_artwork_index(&env, glyph_index)?;
+
+        Ok(env
+            .storage()
+            .persistent()
+            .get::<Storage, RoyaltySplit>(&Storage::GlyphAuthorSplit(glyph_index)))
+    }
+    pub fn glyph_author_split_set(
+        env: Env,
+        glyph_index: u32,
+        author_split: Option<RoyaltySplit>,
+    ) -> Result<(), Error> {
+        let glyph_index = get_artwork_index(&env, glyph_index)?;
+        let Glyph { author, .. } = get_glyph(&env, glyph_index).ok_or(Error::GlyphNotMinted)?;
+
+        author.require_auth();
+
+        let author_split_key = Storage::GlyphAuthorSplit(glyph_index);
+
+        if let Some(RoyaltySplit {
+            immutable: true, ..
+        }) = env
+            .storage()
+            .persistent()
+            .get::<Storage, RoyaltySplit>(&author_split_key)
+        {
+            return Err(Error::AuthorSplitImmutable);
+        }
+
+        match &author_split {
+            Some(author_split) => {
+                check_author_split(author_split)?;
+
+                env.storage()
+                    .persistent()
+                    .set::<Storage, RoyaltySplit>(&author_split_key, author_split);
+            }
+            None => {
+                env.storage()
+                    .persistent()
+                    .remove::<Storage>(&author_split_key);
+            }
+        }
+
+        env.events().publish(
+            (Symbol::new(&env, "glyph_author_split_set"), glyph_index),
+            author_split,
+        );
+
+        Ok(())
+    }
+    pub fn glyph_metadata_get(env: Env, glyph_index: u32) -> Result<GlyphMetadata, Error> {
+        let glyph_index = get_artwork_index(&env, glyph_index)?;
+
+        env.storage()
+            .persistent()
+            .get::<Storage, GlyphMetadata>(&Storage::GlyphMetadata(glyph_index))
+            .ok_or(Error::GlyphNotMinted)
+    }
+    pub fn glyph_metadata_set(
+        env: Env,
+        glyph_index: u32,
+        title: String,
+        story: String,
+    ) -> Result<(), Error> {
+        let glyph_index = get_artwork_index(&env, glyph_index)?;
+        let Glyph { author, .. } = get_glyph(&env, glyph_index).ok_or(Error::GlyphNotMinted)?;
+
+        author.require_auth();
+
+        check_glyph_metadata(&title, &story)?;
+
+        env.storage().persistent().set::<Storage, GlyphMetadata>(
+            &Storage::GlyphMetadata(glyph_index),
+            &GlyphMetadata {
+                title: title.clone(),
+                story: story.clone(),
+            },
+        );
+
+        env.events().publish(
+            (Symbol::new(&env, "glyph_metadata_set"), glyph_index),
+            (title, story),
+        );
+
+        Ok(())
+    }
+    pub fn glyph_owner_get(env: Env, glyph_index: u32) -> Result<Address, Error> {
+        env.storage()
+            .persistent()
+            .get::<Storage, Address>(&Storage::GlyphOwner(glyph_index))
+            .ok_or(Error::GlyphNotMinted)
+    }
+    pub fn glyph_owner_transfer(env: Env, glyph_index: u32, to: Address) -> Result<(), Error> {
+        transfer_glyph(&env, &None, glyph_index, to)
+    }
+    pub fn glyph_owner_transfer_from(
+        env: Env,
+        spender: Address,
+        glyph_index: u32,
+        to: Address,
+    ) -> Result<(), Error> {
+        transfer_glyph(&env, &Some(spender), glyph_index, to)
+    }
+    pub fn glyph_burn(env: Env, glyph_index: u32) -> Result<(), Error> {
+        burn_glyph(&env, &None, glyph_index)
+    }
+    pub fn glyph_burn_from(env: Env, spender: Address, glyph_index: u32) -> Result<(), Error> {
+        burn_glyph(&env, &Some(spender), glyph_index)
+    }
+    pub fn name(env: Env) -> String {
+        String::from_str(&env, NFT_NAME)
+    }
+    pub fn symbol(env: Env) -> String {
+        String::from_str(&env, NFT_SYMBOL)
+    }
+    pub fn token_uri(env: Env, token_id: u32) -> Result<String, Error> {
+        if !env
+            .storage()
+            .persistent()
+            .has::<Storage>(&Storage::Glyph(token_id))
+        {
+            return Err(Error::GlyphNotMinted);
+        }
+
+        let base_uri = env
+            .storage()
+            .instance()
+            .get::<Storage, String>(&Storage::GlyphBaseUri)
+            .unwrap_or(String::from_str(&env, ""));
+
+        // the base uri followed by the glyph index in decimal
+        let mut uri = [0u8; MAX_GLYPH_BASE_URI_LENGTH as usize + 10];
+        let mut uri_length = base_uri.len() as usize;
+
+        base_uri.copy_into_slice(&mut uri[..uri_length]);
+
+        let mut digits = [0u8; 10];
+        let mut digits_length = 0;
+        let mut index = token_id;
+
+        loop {
+            digits[digits_length] = b'0' + (index % 10) as u8;
+            digits_length += 1;
+            index /= 10;
+
+            if index == 0 {
+                break;
+            }
+        }
+
+        for digit in digits[..digits_length].iter().rev() {
+            uri[uri_length] = *digit;
+            uri_length += 1;
+        }
+
+        Ok(String::from_bytes(&env, &uri[..uri_length]))
+    }
+    pub fn balance(env: Env, owner: Address) -> u32 {
+        env.storage()
+            .persistent()
+            .get::<Storage, u32>(&Storage::GlyphBalance(owner))
+            .unwrap_or(0)
+    }
+    pub fn owner_of(env: Env, token_id: u32) -> Result<Address, Error> {
+        Self::glyph_owner_get(env, token_id)
+    }
+    pub fn transfer(env: Env, from: Address, to: Address, token_id: u32) -> Result<(), Error> {
+        from.require_auth();
+
+        if Self::glyph_owner_get(env.clone(), token_id)? != from {
+            return Err(Error::GlyphOwnerIncorrect);
+        }
+
+        set_glyph_owner(&env, token_id, Some(&from), Some(&to));
+
+        env.events()
+            .publish((Symbol::new(&env, "glyph_owner_transfer"), to), token_id);
+
+        Ok(())
+    }
+    pub fn transfer_from(
+        env: Env,
+        spender: Address,
+        from: Address,
+        to: Address,
+        token_id: u32,
+    ) -> Result<(), Error> {
+        spender.require_auth();
+
+        if Self::glyph_owner_get(env.clone(), token_id)? != from {
+            return Err(Error::GlyphOwnerIncorrect);
+        }
+
+        if !is_glyph_approved(&env, &from, &spender, token_id) {
+            return Err(Error::ApprovalInsufficient);
+        }
+
+        set_glyph_owner(&env, token_id, Some(&from), Some(&to));
+
+        env.events()
+            .publish((Symbol::new(&env, "glyph_owner_transfer"), to), token_id);
+
+        Ok(())
+    }
+    pub fn approve(
+        env: Env,
+        approver: Address,
+        approved: Address,
+        token_id: u32,
+        live_until_ledger: u32,
+    ) -> Result<(), Error> {
+        approver.require_auth();
+
+        let owner = Self::glyph_owner_get(env.clone(), token_id)?;
+
+        // the owner or one of their operators
+        if approver != owner && !is_operator_approved(&env, &owner, &approver) {
+            return Err(Error::ApprovalInsufficient);
+        }
+
+        set_approval(
+            &env,
+            &Storage::GlyphApproval(token_id),
+            &approved,
+            live_until_ledger,
+        )?;
+
+        if live_until_ledger == 0 {
+            env.events()
+                .publish((Symbol::new(&env, "revoke"), approver, token_id), approved);
+        } else {
+            env.events().publish(
+                (Symbol::new(&env, "approve"), approver, token_id),
+                (approved, live_until_ledger),
+            );
+        }
+
+        Ok(())
+    }
+    pub fn approve_for_all(
+        env: Env,
+        owner: Address,
+        operator: Address,
+        live_until_ledger: u32,
+    ) -> Result<(), Error> {
+        owner.require_auth();
+
+        let operator_key = Storage::Operator(owner.clone(), operator.clone());
+
+        // operators act for all of the owner's glyphs and colors, a live_until_ledger of 0 revokes
+        if live_until_ledger == 0 {
+            env.storage().persistent().remove::<Storage>(&operator_key);
+
+            env.events()
+                .publish((Symbol::new(&env, "revoke_for_all"), owner), operator);
+        } else if live_until_ledger < env.ledger().sequence() {
+            return Err(Error::ApprovalExpirationInvalid);
+        } else {
+            env.storage()
+                .persistent()
+                .set::<Storage, u32>(&operator_key, &live_until_ledger);
+
+            env.events().publish(
+                (Symbol::new(&env, "approve_for_all"), owner),
+                (operator, live_until_ledger),
+            );
+        }
+
+        Ok(())
+    }
+    pub fn get_approved(env: Env, token_id: u32) -> Result<Option<Address>, Error> {
+        Ok(Self::glyph_approval_get(env, token_id)?.map(|(approved, _)| approved))
+    }
+    pub fn is_approved_for_all(env: Env, owner: Address, operator: Address) -> bool {
+        is_operator_approved(&env, &owner, &operator)
+    }
+    pub fn color_approve(
+        env: Env,
+        approver: Address,
+        approved: Address,
+        color: u32,
+        live_until_ledger: u32,
+    ) -> Result<(), Error> {
+        approver.require_auth();
+
+        let owner = Self::color_owner_get(env.clone(), color)?;
+
+        // the owner or one of their operators
+        if approver != owner && !is_operator_approved(&env, &owner, &approver) {
+            return Err(Error::ApprovalInsufficient);
+        }
+
+        set_approval(
+            &env,
+            &Storage::ColorApproval(color),
+            &approved,
+            live_until_ledger,
+        )?;
+
+        if live_until_ledger == 0 {
+            env.events().publish(
+                (Symbol::new(&env, "color_revoke"), approver, color),
+                approved,
+            );
+        } else {
+            env.events().publish(
+                (Symbol::new(&env, "color_approve"), approver, color),
+                (approved, live_until_ledger),
+            );
+        }
+
+        Ok(())
+    }
+    pub fn glyph_approval_get(env: Env, glyph_index: u32) -> Result<Option<(Address, u32)>, Error> {
+        Self::glyph_owner_get(env.clone(), glyph_index)?;
+
+        Ok(get_approval(&env, &Storage::GlyphApproval(glyph_index)))
+    }
+    pub fn color_approval_get(env: Env, color: u32) -> Result<Option<(Address, u32)>, Error> {
+        Self::color_owner_get(env.clone(), color)?;
+
+        Ok(get_approval(&env, &Storage::ColorApproval(color)))
+    }
+    pub fn operator_approval_get(env: Env, owner: Address, operator: Address) -> Option<u32> {
+        env.storage()
+            .persistent()
+            .get::<Storage, u32>(&Storage::Operator(owner, operator))
+            .filter(|live_until_ledger| *live_until_ledger >= env.ledger().sequence())
+    }
+
+    pub fn sale_quote(
+        env: Env,
+        glyph_index: u32,
+        sac: Address,
+        amount: i128,
+    ) -> Result<SaleQuote, Error> {
+        check_asset(&env, &sac, &amount)?;
+        check_sale_amount(&env, glyph_index, &amount)?;
+
+        let SaleShares {
+            author_amounts,
+            color_shares:
+                ColorShares {
+                    color_amounts,
+                    remainder_fee,
+                    total: color_owner_amount,
+                },
+            seller_amount,
+        } = get_sale_shares(&env, glyph_index, &amount)?;
+
+        let (remainder_address, remainder_fee) = match remainder_fee {
+            Some((remainder_address, remainder_fee)) => (Some(remainder_address), remainder_fee),
+            None => (None, 0),
+        };
+
+        Ok(SaleQuote {
+            author_amounts,
+            color_amounts,
+            color_owner_amount,
+            remainder_address,
+            remainder_fee,
+            seller_amount,
+        })
+    }
+    pub fn offer_sell_glyph(env: Env, sell: u32, buy: OfferBuy) -> Result<Option<Address>, Error> {
+        offer_glyph(&env, &None, sell, buy)
+    }
+    pub fn offer_sell_glyph_from(
+        env: Env,
+        spender: Address,
+        sell: u32,
+        buy: OfferBuy,
+    ) -> Result<Option<Address>, Error> {
+        offer_glyph(&env, &Some(spender), sell, buy)
+    }
+    pub fn offer_sell_asset(
+        env: Env,
+        sell: OfferSellAsset,
+        buy: u32,
+    ) -> Result<Option<Address>, Error> {
+        let OfferSellAsset(owner, sac, amount) = sell.clone();
+
+        owner.require_auth();
+
+        check_asset(&env, &sac, &amount)?;
+        check_sale_amount(&env, buy, &amount)?;
+
+        let open_glyph_buy_now_offers_key = Storage::OfferSellGlyph(buy);
+        let open_glyph_buy_now_offers = env
+            .storage()
+            .persistent()
+            .get::<Storage, Vec<OfferBuy>>(&open_glyph_buy_now_offers_key)
+            .unwrap_or(Vec::new(&env));
+
+        let offer_buy = OfferBuy::Asset(sac.clone(), amount);
+
+        match open_glyph_buy_now_offers.binary_search(&offer_buy) {
+            // Found a matching open counter offer. Take it
+            Ok(_index) if check_offer_sell_glyph_spender(&env, buy, &offer_buy) => {
+                let buy_glyph_owner_key = Storage::GlyphOwner(buy);
+                let buy_glyph_owner = env
+                    .storage()
+                    .persistent()
+                    .get::<Storage, Address>(&buy_glyph_owner_key)
+                    .ok_or(Error::GlyphNotMinted)?;
+
+                // Send the amount to the contract for passive claiming later
+                let received_amount = receive_asset(&env, &sac, &owner, &amount)?;
+
+                settle_sale(&env, buy, &buy_glyph_owner, &sac, &received_amount)?;
+
+                // swap glyph ownership, which removes all open buy glyph sell offers
+                set_glyph_owner(&env, buy, Some(&buy_glyph_owner), Some(&owner));
+
+                env.events().publish(
+                    (Symbol::new(&env, "offer_sell_asset"), sell, buy),
+                    Some(owner.clone()),
+                );
+
+                Ok(Some(owner))
+            }
+            // No matching open counter offer. Add to buy glyph offers
+            _ => {
+                let offer_sell_asset_key = Storage::OfferSellAsset(buy, sac.clone(), amount);
+
+                let mut offers = env
+                    .storage()
+                    .persistent()
+                    .get::<Storage, Vec<Address>>(&offer_sell_asset_key)
+                    .unwrap_or(Vec::new(&env));
+
+                match offers.binary_search(&owner) {
+                    Ok(_index) => return Err(Error::OfferDuplicate),
+                    Err(index) => offers.insert(index, owner.clone()),
+                }
+
+                env.storage()
+                    .persistent()
+                    .set::<Storage, Vec<Address>>(&offer_sell_asset_key, &offers);
+
+                add_offer_sell_asset_bid(&env, buy, &sell)?;
+
+                // transfer the asset to the contract for auto matching later
+                let received_amount = receive_asset(&env, &sac, &owner, &amount)?;
+
+                env.storage().persistent().set::<Storage, i128>(
+                    &Storage::OfferSellAssetEscrow(buy, sac.clone(), amount, owner.clone()),
+                    &received_amount,
+                );
+
+                env.events().publish(
+                    (Symbol::new(&env, "offer_sell_asset"), sell, buy),
+                    None::<Address>,
+                );
+
+                Ok(None)
+            }
+        }
+    }
+    pub fn offer_sell_glyph_remove(
+        env: Env,
+        sell: u32,
+        buy: Option<OfferBuy>,
+    ) -> Result<(), Error> {
+        remove_glyph_offer(&env, &None, sell, buy)
+    }
+    pub fn offer_sell_glyph_remove_from(
+        env: Env,
+        spender: Address,
+        sell: u32,
+        buy: Option<OfferBuy>,
+    ) -> Result<(), Error> {
+        remove_glyph_offer(&env, &Some(spender), sell, buy)
+    }
+    pub fn offer_sell_asset_remove(env: Env, sell: OfferSellAsset, buy: u32) -> Result<(), Error> {
+        let OfferSellAsset(owner, sac, amount) = sell.clone();
+
+        owner.require_auth();
+
+        let offer_sell_asset_key = Storage::OfferSellAsset(buy, sac.clone(), amount);
+
+        let mut offers = env
+            .storage()
+            .persistent()
+            .get::<Storage, Vec<Address>>(&offer_sell_asset_key)
+            .unwrap_or(Vec::new(&env));
+
+        match offers.binary_search(&owner) {
+            Ok(index) => {
+                offers.remove(index);
+            }
+            Err(_index) => return Err(Error::OfferNotFound),
+        }
+
+        env.storage()
+            .persistent()
+            .set::<Storage, Vec<Address>>(&offer_sell_asset_key, &offers);
+
+        remove_offer_sell_asset_bid(&env, buy, &sell);
+
+        let offer_sell_asset_escrow_key =
+            Storage::OfferSellAssetEscrow(buy, sac.clone(), amount, owner.clone());
+        let escrowed_amount = env
+            .storage()
+            .persistent()
+            .get::<Storage, i128>(&offer_sell_asset_escrow_key)
+            .unwrap_or(amount);
+
+        env.storage()
+            .persistent()
+            .remove::<Storage>(&offer_sell_asset_escrow_key);
+
+        // refund the asset back to the user from the contract
+        let token_client = token::TokenClient::new(&env, &sac);
+
+        token_client.transfer(&env.current_contract_address(), &owner, &escrowed_amount);
+
+        env.events().publish(
+            (Symbol::new(&env, "offer_sell_asset_remove"), sell, buy),
+            (),
+        );
+
+        Ok(())
+    }
+    pub fn offer_sell_glyph_get(
+        env: Env,
+        sell: u32,
+        buy: Option<OfferBuy>,
+    ) -> Result<Option<u32>, Error> {
+        let offers = env
+            .storage()
+            .persistent()
+            .get::<Storage, Vec<OfferBuy>>(&Storage::OfferSellGlyph(sell))
+            .unwrap_or(Vec::new(&env));
+
+        match buy {
+            Some(buy) => match offers.binary_search(&buy) {
+                Ok(index) => Ok(Some(index)),
+                Err(_index) => Ok(None),
+            },
+            None => {
+                if offers.is_empty() {
+                    Ok(None)
+                } else {
+                    Ok(Some(offers.len()))
+                }
+            }
+        }
+    }
+    pub fn offer_sell_asset_get(
+        env: Env,
+        sell: OfferSellAssetGet,
+        buy: u32,
+    ) -> Result<Option<u32>, Error> {
+        let OfferSellAssetGet(owner, sell, amount) = sell;
+
+        let offers = env
+            .storage()
+            .persistent()
+            .get::<Storage, Vec<Address>>(&Storage::OfferSellAsset(buy, sell, amount))
+            .unwrap_or(Vec::new(&env));
+
+        match owner {
+            Some(owner) => match offers.binary_search(&owner) {
+                Ok(index) => Ok(Some(index)),
+                Err(_index) => Ok(None),
+            },
+            None => {
+                if offers.is_empty() {
+                    Ok(None)
+                } else {
+                    Ok(Some(offers.len()))
+                }
+            }
+        }
+    }
+
+    pub fn royalties_get(env: Env, owner: Address, sac: Address) -> Result<i128, Error> {
+        let royalties = env
+            .storage()
+            .persistent()
+            .get::<Storage, i128>(&Storage::Royalties(owner, sac))
+            .unwrap_or(0);
+
+        Ok(royalties)
+    }
+    pub fn royalties_assets_get(env: Env, owner: Address) -> Result<Vec<Address>, Error> {
+        let royalties_assets = env
+            .storage()
+            .persistent()
+            .get::<Storage, Vec<Address>>(&Storage::RoyaltiesAssets(owner))
+            .unwrap_or(Vec::new(&env));
+
+        Ok(royalties_assets)
+    }
+    pub fn royalties_claim(env: Env, owner: Address, sac: Address) -> Result<i128, Error> {
+        let royalties = claim_royalties(&env, &owner, &sac, &owner);
+
+        if royalties == 0 {
+            return Err(Error::NoRoyaltiesToClaim);
+        }
+
+        Ok(royalties)
+    }
+    // claim several assets at once, defaulting to every asset the owner has royalties in
+    pub fn royalties_claim_batch(
+        env: Env,
+        owner: Address,
+        sacs: Option<Vec<Address>>,
+        to: Option<Address>,
+    ) -> Result<Map<Address, i128>, Error> {
+        // sending royalties anywhere but the owner requires their say so
+        let to = match to {
+            Some(to) => {
+                owner.require_auth();
+                to
+            }
+            None => owner.clone(),
+        };
+
+        let sacs = match sacs {
+            Some(sacs) => sacs,
+            None => Self::royalties_assets_get(env.clone(), owner.clone())?,
+        };
+
+        let mut claimed = Map::new(&env);
+
+        for sac in sacs.iter() {
+            let royalties = claim_royalties(&env, &owner, &sac, &to);
+
+            if royalties > 0 {
+                claimed.set(sac, royalties);
+            }
+        }
+
+        Ok(claimed)
+    }
+
+    pub fn color_royalties_get(
+        env: Env,
+        color: u32,
+        glyph_index: u32,
+        sac: Address,
+    ) -> Result<i128, Error> {
+        let (royalties, _) = get_color_royalties(&env, color, glyph_index, &sac);
+
+        Ok(royalties)
+    }
+    // anyone can collect as royalties are credited to whoever owned the color when they accrued
+    pub fn color_royalties_collect(
+        env: Env,
+        color: u32,
+        glyph_indexes: Vec<u32>,
+        sac: Address,
+    ) -> Result<i128, Error> {
+        let color_owner = env
+            .storage()
+            .persistent()
+            .get::<Storage, Address>(&Storage::ColorOwner(color))
+            .ok_or(Error::ColorNotClaimed)?;
+
+        let mut royalties = 0;
+
+        for glyph_index in glyph_indexes.iter() {
+            let (glyph_royalties, cumulative) = get_color_royalties(&env, color, glyph_index, &sac);
+
+            let collected_key = Storage::ColorRoyaltiesCollected(glyph_index, color, sac.clone());
+
+            if let Some(ColorRoyalties { owner, .. }) = env
+                .storage()
+                .persistent()
+                .get::<Storage, ColorRoyalties>(&collected_key)
+            {
+                if glyph_royalties == 0 {
+                    continue;
+                }
+
+                update_royalties(&env, &owner, &sac, &glyph_royalties);
+
+                // later sales accrue to the current owner
+                env.storage().persistent().set::<Storage, ColorRoyalties>(
+                    &collected_key,
+                    &ColorRoyalties {
+                        owner: color_owner.clone(),
+                        collected: cumulative,
+                    },
+                );
+
+                royalties += glyph_royalties;
+            }
+        }
+
+        if royalties == 0 {
+            return Err(Error::NoRoyaltiesToClaim);
+        }
+
+        env.events().publish(
+            (Symbol::new(&env, "color_royalties_collect"), color),
+            (sac, royalties),
+        );
+
+        Ok(royalties)
+    }
+}
+
+#[contractimpl]
+impl CustomAccountInterface for Contract {
+    type Error = Error;
+    type Signature = Option<Vec<Val>>;
+
+    #[allow(non_snake_case)]
+    fn __check_auth(
+        env: Env,
+        _signature_payload: Hash<32>,
+        _signatures: Option<Vec<Val>>,
+        _auth_contexts: Vec<Context>,
+    ) -> Result<(), Error> {
+        let admin = env
+            .storage()
+            .instance()
+            .get::<Storage, Address>(&Storage::Admin)
+            .ok_or(Error::NotInitialized)?;
+
+        admin.require_auth_for_args(vec![&env]);
+
+        Ok(())
+    }
+}
+
+fn pay_fee(env: &Env, fee_key: Storage, source: &Address) -> Result<(), Error> {
+    let fee_address = env
+        .storage()
+        .instance()
+        .get::<Storage, Address>(&Storage::FeeAddress)
+        .ok_or(Error::NotInitialized)?;
+    let fee_amount = env
+        .storage()
+        .instance()
+        .get::<Storage, i128>(&fee_key)
+        .ok_or(Error::NotInitialized)?;
+    let fee_sac = env
+        .storage()
+        .instance()
+        .get::<Storage, Address>(&Storage::FeeSAC)
+        .ok_or(Error::NotInitialized)?;
+    let fee_client = token::TokenClient::new(&env, &fee_sac);
+
+    source.require_auth();
+
+    fee_client.transfer(&source, &fee_address, &fee_amount);
+
+    Ok(())
+}
+
+fn get_asset_allowlist(env: &Env) -> Map<Address, Option<i128>> {
+    env.storage()
+        .instance()
+        .get::<Storage, Map<Address, Option<i128>>>(&Storage::AssetAllowlist)
+        .unwrap_or(Map::new(env))
+}
+
+// only allowlisted SACs may be escrowed or end up in the royalties ledger
+fn check_asset(env: &Env, sac: &Address, amount: &i128) -> Result<(), Error> {
+    match get_asset_allowlist(env).get(sac.clone()) {
+        Some(Some(min_amount)) if *amount < min_amount => Err(Error::AssetAmountTooLow),
+        Some(_) => Ok(()),
+        None => Err(Error::AssetNotAllowed),
+    }
+}
+
+// measure the actual balance delta so fee-on-transfer or rebasing tokens can't leave
+// the contract holding less than it owes in escrow and royalties
+fn receive_asset(env: &Env, sac: &Address, from: &Address, amount: &i128) -> Result<i128, Error> {
+    let token_client = token::TokenClient::new(env, sac);
+    let contract = env.current_contract_address();
+
+    let balance_before = token_client.balance(&contract);
+
+    token_client.transfer(from, &contract, amount);
+
+    let received_amount = token_client.balance(&contract) - balance_before;
+
+    if received_amount < *amount {
+        return Err(Error::AssetReceivedShort);
+    }
+
+    Ok(received_amount)
+}
+
+// a split or a non-default rate changes the author's royalties so the author has to sign for
+// them, an author minting for themselves already signs as the source
+fn require_author_auth(
+    source: &Address,
+    author: &Address,
+    author_split: &Option<RoyaltySplit>,
+    author_royalty_rate: &Option<i128>,
+) {
+    if (author_split.is_some() || author_royalty_rate.is_some()) && author != source {
+        author.require_auth();
+    }
+}
+
+// a contracttype can't hold an optional RoyaltySplit so mint options carry its fields
+fn get_mint_author_split(
+    recipients: Option<Vec<(Address, u32)>>,
+    immutable: bool,
+) -> Option<RoyaltySplit> {
+    recipients.map(|recipients| RoyaltySplit {
+        recipients,
+        immutable,
+    })
+}
+
+fn check_author_split(author_split: &RoyaltySplit) -> Result<(), Error> {
+    let RoyaltySplit { recipients, .. } = author_split;
+
+    if recipients.is_empty() || recipients.len() > MAX_AUTHOR_SPLIT_RECIPIENTS {
+        return Err(Error::AuthorSplitInvalid);
+    }
+
+    let mut basis_points_total = 0;
+
+    for (index, (recipient, basis_points)) in recipients.iter().enumerate() {
+        if basis_points == 0 || basis_points > 10_000 {
+            return Err(Error::AuthorSplitInvalid);
+        }
+
+        // each recipient may only appear once
+        for (other_recipient, _) in recipients.iter().skip(index + 1) {
+            if other_recipient == recipient {
+                return Err(Error::AuthorSplitInvalid);
+            }
+        }
+
+        basis_points_total += basis_points;
+    }
+
+    if basis_points_total != 10_000 {
+        return Err(Error::AuthorSplitInvalid);
+    }
+
+    Ok(())
+}
+
+// children are stored one entry each so a popular parent never outgrows an entry
+fn store_glyph_lineage(env: &Env, glyph_index: u32, glyph_lineage: &GlyphLineage) {
+    for (parent, _) in glyph_lineage.parents.iter() {
+        let children_key = Storage::GlyphChildren(parent);
+        let children = env
+            .storage()
+            .persistent()
+            .get::<Storage, u32>(&children_key)
+            .unwrap_or(0);
+
+        env.storage()
+            .persistent()
+            .set::<Storage, u32>(&Storage::GlyphChild(parent, children), &glyph_index);
+        env.storage()
+            .persistent()
+            .set::<Storage, u32>(&children_key, &(children + 1));
+    }
+
+    env.storage()
+        .persistent()
+        .set::<Storage, GlyphLineage>(&Storage::GlyphLineage(glyph_index), glyph_lineage);
+}
+
+fn update_glyph_dependents(env: &Env, glyph_index: u32, added: bool) {
+    let dependents_key = Storage::GlyphDependents(glyph_index);
+    let dependents = env
+        .storage()
+        .persistent()
+        .get::<Storage, u32>(&dependents_key)
+        .unwrap_or(0);
+
+    if added {
+        env.storage()
+            .persistent()
+            .set::<Storage, u32>(&dependents_key, &(dependents + 1));
+    } else if dependents > 1 {
+        env.storage()
+            .persistent()
+            .set::<Storage, u32>(&dependents_key, &(dependents - 1));
+    } else {
+        env.storage()
+            .persistent()
+            .remove::<Storage>(&dependents_key);
+    }
+}
+
+fn get_glyph_lineage(env: &Env, parents: &Vec<u32>) -> Result<GlyphLineage, Error> {
+    if parents.is_empty() || parents.len() > MAX_GLYPH_PARENTS {
+        return Err(Error::GlyphParentsInvalid);
+    }
+
+    let mut glyph_lineage_parents = Vec::new(env);
+
+    for (index, parent) in parents.iter().enumerate() {
+        if parents.first_index_of(parent) != Some(index as u32) {
+            return Err(Error::GlyphParentsInvalid);
+        }
+
+        let Glyph { author, .. } = get_glyph(env, parent).ok_or(Error::GlyphNotMinted)?;
+
+        glyph_lineage_parents.push_back((parent, author));
+    }
+
+    // snapshot the lineage rate like the other royalty rates
+    Ok(GlyphLineage {
+        parents: glyph_lineage_parents,
+        royalty_rate: get_glyph_lineage_royalty_rate(env),
+    })
+}
+
+// lineage royalties are opt in, until the admin sets a rate with glyph_lineage_set
+// children record their parents but sales pay out as if they had none
+fn get_glyph_lineage_royalty_rate(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get::<Storage, i128>(&Storage::GlyphLineageRoyaltyRate)
+        .unwrap_or(0)
+}
+
+// snapshot the royalty rates so later admin updates don't change this glyph's economics
+fn get_royalty_rates(env: &Env, author_royalty_rate: Option<i128>) -> Result<(i128, i128), Error> {
+    let color_owner_royalty_rate = env
+        .storage()
+        .instance()
+        .get::<Storage, i128>(&Storage::ColorOwnerRoyaltyRate)
+        .ok_or(Error::NotInitialized)?;
+    let glyph_author_royalty_rate = env
+        .storage()
+        .instance()
+        .get::<Storage, i128>(&Storage::GlyphAuthorRoyaltyRate)
+        .ok_or(Error::NotInitialized)?;
+    let author_royalty_rate = match author_royalty_rate {
+        Some(author_royalty_rate) => {
+            let (min, max) = env
+                .storage()
+                .instance()
+                .get::<Storage, (i128, i128)>(&Storage::GlyphAuthorRoyaltyBounds)
+                .unwrap_or((glyph_author_royalty_rate, glyph_author_royalty_rate));
+
+            if author_royalty_rate < min || author_royalty_rate > max {
+                return Err(Error::RoyaltyRateOutOfRange);
+            }
+
+            author_royalty_rate
+        }
+        None => glyph_author_royalty_rate,
+    };
+
+    check_royalty_rates(color_owner_royalty_rate, author_royalty_rate)?;
+
+    Ok((author_royalty_rate, color_owner_royalty_rate))
+}
+
+fn next_glyph_index(env: &Env) -> u32 {
+    // settle where balance tracking starts before the first mint after an upgrade
+    get_glyph_balance_index(env);
+
+    let glyph_index = env
+        .storage()
+        .instance()
+        .get::<Storage, u32>(&Storage::GlyphIndex)
+        .unwrap_or(0)
+        + 1;
+
+    env.storage()
+        .instance()
+        .set::<Storage, u32>(&Storage::GlyphIndex, &glyph_index);
+
+    glyph_index
+}
+
+// glyph indexes from here up are counted in balances, an upgraded contract starts counting at
+// its next mint and glyph_balances_migrate works down through the older ones
+fn get_glyph_balance_index(env: &Env) -> u32 {
+    if let Some(glyph_balance_index) = env
+        .storage()
+        .instance()
+        .get::<Storage, u32>(&Storage::GlyphBalanceIndex)
+    {
+        return glyph_balance_index;
+    }
+
+    let glyph_balance_index = env
+        .storage()
+        .instance()
+        .get::<Storage, u32>(&Storage::GlyphIndex)
+        .unwrap_or(0)
+        + 1;
+
+    env.storage()
+        .instance()
+        .set::<Storage, u32>(&Storage::GlyphBalanceIndex, &glyph_balance_index);
+
+    glyph_balance_index
+}
+
+fn burn_glyph(env: &Env, spender: &Option<Address>, glyph_index: u32) -> Result<(), Error> {
+    let glyph_owner_key = Storage::GlyphOwner(glyph_index);
+
+    let glyph_owner = env
+        .storage()
+        .persistent()
+        .get::<Storage, Address>(&glyph_owner_key)
+        .ok_or(Error::GlyphNotMinted)?;
+
+    require_glyph_spender(env, &glyph_owner, spender, glyph_index)?;
+
+    let Glyph { encoding, .. } = get_glyph(env, glyph_index).ok_or(Error::GlyphNotMinted)?;
+
+    // recolors, composites and later editions read this glyph's pixels so it has to outlive them
+    if env
+        .storage()
+        .persistent()
+        .get::<Storage, u32>(&Storage::GlyphDependents(glyph_index))
+        .unwrap_or(0)
+        > 0
+    {
+        return Err(Error::GlyphHasDependents);
+    }
+
+    // cancel every swap offer asking for this glyph, set_glyph_owner cancels the owner's listings
+    let offer_buy_glyph_key = Storage::OfferBuyGlyph(glyph_index);
+
+    for sell in env
+        .storage()
+        .persistent()
+        .get::<Storage, Vec<u32>>(&offer_buy_glyph_key)
+        .unwrap_or(Vec::new(env))
+        .iter()
+    {
+        let offer_sell_glyph_key = Storage::OfferSellGlyph(sell);
+        let mut offers = env
+            .storage()
+            .persistent()
+            .get::<Storage, Vec<OfferBuy>>(&offer_sell_glyph_key)
+            .unwrap_or(Vec::new(env));
+
+        if let Ok(index) = offers.binary_search(OfferBuy::Glyph(glyph_index)) {
+            offers.remove(index);
+        }
+
+        remove_offer_sell_glyph_spender(env, sell, &OfferBuy::Glyph(glyph_index));
+
+        if offers.is_empty() {
+            env.storage()
+                .persistent()
+                .remove::<Storage>(&offer_sell_glyph_key);
+        } else {
+            env.storage()
+                .persistent()
+                .set::<Storage, Vec<OfferBuy>>(&offer_sell_glyph_key, &offers);
+        }
+    }
+
+    env.storage()
+        .persistent()
+        .remove::<Storage>(&offer_buy_glyph_key);
+
+    // refund every escrowed bid for this glyph
+    let offer_sell_asset_bids_key = Storage::OfferSellAssetBids(glyph_index);
+
+    for bid in env
+        .storage()
+        .persistent()
+        .get::<Storage, Vec<OfferSellAsset>>(&offer_sell_asset_bids_key)
+        .unwrap_or(Vec::new(env))
+        .iter()
+    {
+        let OfferSellAsset(owner, sac, amount) = bid.clone();
+
+        env.storage()
+            .persistent()
+            .remove::<Storage>(&Storage::OfferSellAsset(glyph_index, sac.clone(), amount));
+
+        let offer_sell_asset_escrow_key =
+            Storage::OfferSellAssetEscrow(glyph_index, sac.clone(), amount, owner.clone());
+        let escrowed_amount = env
+            .storage()
+            .persistent()
+            .get::<Storage, i128>(&offer_sell_asset_escrow_key)
+            .unwrap_or(amount);
+
+        env.storage()
+            .persistent()
+            .remove::<Storage>(&offer_sell_asset_escrow_key);
+
+        let token_client = token::TokenClient::new(env, &sac);
+
+        token_client.transfer(&env.current_contract_address(), &owner, &escrowed_amount);
+
+        env.events().publish(
+            (
+                Symbol::new(env, "offer_sell_asset_remove"),
+                bid,
+                glyph_index,
+            ),
+            (),
+        );
+    }
+
+    env.storage()
+        .persistent()
+        .remove::<Storage>(&offer_sell_asset_bids_key);
+
+    // remove the pixel data, palette and ownership, the split and lineage stay so
+    // royalties keep cascading through this glyph
+    match encoding {
+        GlyphEncoding::Tiled(tiles) => {
+            for tile in 0..tiles {
+                env.storage()
+                    .persistent()
+                    .remove::<Storage>(&Storage::GlyphTile(glyph_index, tile));
+            }
+        }
+        // the glyphs this one read its pixels from can be burned once it's gone
+        GlyphEncoding::Recolor(_) | GlyphEncoding::Composite(..) => {
+            if let Some(GlyphLineage { parents, .. }) = env
+                .storage()
+                .persistent()
+                .get::<Storage, GlyphLineage>(&Storage::GlyphLineage(glyph_index))
+            {
+                for (parent, _) in parents.iter() {
+                    update_glyph_dependents(env, parent, false);
+                }
+            }
+        }
+        GlyphEncoding::Edition(first_edition) => {
+            update_glyph_dependents(env, first_edition, false);
+        }
+        _ => {}
+    }
+
+    env.storage()
+        .persistent()
+        .remove::<Storage>(&Storage::GlyphAnimation(glyph_index));
+    env.storage()
+        .persistent()
+        .remove::<Storage>(&Storage::GlyphMetadata(glyph_index));
+    env.storage()
+        .persistent()
+        .remove::<Storage>(&Storage::GlyphPalette(glyph_index));
+    env.storage()
+        .persistent()
+        .remove::<Storage>(&Storage::Glyph(glyph_index));
+    set_glyph_owner(env, glyph_index, Some(&glyph_owner), None);
+
+    let glyph_hash_key = Storage::GlyphHash(glyph_index);
+    let glyph_burn_frees_hash = env
+        .storage()
+        .instance()
+        .get::<Storage, bool>(&Storage::GlyphBurnFreesHash)
+        .unwrap_or(false);
+
+    // otherwise the hash stays taken so the image can't be minted again
+    if glyph_burn_frees_hash {
+        if let Some(glyph_hash) = env
+            .storage()
+            .persistent()
+            .get::<Storage, BytesN<32>>(&glyph_hash_key)
+        {
+            env.storage()
+                .persistent()
+                .remove::<Storage>(&Storage::GlyphIndexHashMap(glyph_hash));
+        }
+    }
+
+    env.storage()
+        .persistent()
+        .remove::<Storage>(&glyph_hash_key);
+
+    env.events().publish(
+        (Symbol::new(env, "glyph_burn"), glyph_owner),
+        (glyph_index, glyph_burn_frees_hash),
+    );
+
+    Ok(())
+}
+
+// swap offers are indexed by the glyph they ask for, so burning it can clear them
+fn add_offer_buy_glyph(env: &Env, sell: u32, buy: u32) -> Result<(), Error> {
+    let offer_buy_glyph_key = Storage::OfferBuyGlyph(buy);
+    let mut offers = env
+        .storage()
+        .persistent()
+        .get::<Storage, Vec<u32>>(&offer_buy_glyph_key)
+        .unwrap_or(Vec::new(env));
+
+    if let Err(index) = offers.binary_search(sell) {
+        if offers.len() >= MAX_GLYPH_SWAP_OFFERS {
+            return Err(Error::OfferLimitReached);
+        }
+
+        offers.insert(index, sell);
+    }
+
+    env.storage()
+        .persistent()
+        .set::<Storage, Vec<u32>>(&offer_buy_glyph_key, &offers);
+
+    Ok(())
+}
+
+fn remove_offer_buy_glyph(env: &Env, sell: u32, buy: u32) {
+    let offer_buy_glyph_key = Storage::OfferBuyGlyph(buy);
+    let mut offers = env
+        .storage()
+        .persistent()
+        .get::<Storage, Vec<u32>>(&offer_buy_glyph_key)
+        .unwrap_or(Vec::new(env));
+
+    if let Ok(index) = offers.binary_search(sell) {
+        offers.remove(index);
+    }
+
+    if offers.is_empty() {
+        env.storage()
+            .persistent()
+            .remove::<Storage>(&offer_buy_glyph_key);
+    } else {
+        env.storage()
+            .persistent()
+            .set::<Storage, Vec<u32>>(&offer_buy_glyph_key, &offers);
+    }
+}
+
+// escrowed bids are indexed by the glyph they're for, so burning it can refund them
+fn add_offer_sell_asset_bid(env: &Env, buy: u32, bid: &OfferSellAsset) -> Result<(), Error> {
+    let offer_sell_asset_bids_key = Storage::OfferSellAssetBids(buy);
+    let mut bids = env
+        .storage()
+        .persistent()
+        .get::<Storage, Vec<OfferSellAsset>>(&offer_sell_asset_bids_key)
+        .unwrap_or(Vec::new(env));
+
+    if bids.len() >= MAX_GLYPH_BIDS {
+        return Err(Error::OfferLimitReached);
+    }
+
+    bids.push_back(bid.clone());
+
+    env.storage()
+        .persistent()
+        .set::<Storage, Vec<OfferSellAsset>>(&offer_sell_asset_bids_key, &bids);
+
+    Ok(())
+}
+
+fn remove_offer_sell_asset_bid(env: &Env, buy: u32, bid: &OfferSellAsset) {
+    let offer_sell_asset_bids_key = Storage::OfferSellAssetBids(buy);
+    let mut bids = env
+        .storage()
+        .persistent()
+        .get::<Storage, Vec<OfferSellAsset>>(&offer_sell_asset_bids_key)
+        .unwrap_or(Vec::new(env));
+
+    if let Some(index) = bids.first_index_of(bid) {
+        bids.remove(index);
+    }
+
+    if bids.is_empty() {
+        env.storage()
+            .persistent()
+            .remove::<Storage>(&offer_sell_asset_bids_key);
+    } else {
+        env.storage()
+            .persistent()
+            .set::<Storage, Vec<OfferSellAsset>>(&offer_sell_asset_bids_key, &bids);
+    }
+}
+
+// remove all of a glyph's sell offers along with their swap index entries
+fn remove_offer_sell_glyph(env: &Env, sell: u32) {
+    let offer_sell_glyph_key = Storage::OfferSellGlyph(sell);
+
+    if let Some(offers) = env
+        .storage()
+        .persistent()
+        .get::<Storage, Vec<OfferBuy>>(&offer_sell_glyph_key)
+    {
+        for buy in offers.iter() {
+            if let OfferBuy::Glyph(buy) = buy {
+                remove_offer_buy_glyph(env, sell, buy);
+            }
+        }
+
+        env.storage()
+            .persistent()
+            .remove::<Storage>(&offer_sell_glyph_key);
+    }
+
+    let offer_sell_glyph_spender_key = Storage::OfferSellGlyphSpender(sell);
+
+    if env
+        .storage()
+        .persistent()
+        .has(&offer_sell_glyph_spender_key)
+    {
+        env.storage()
+            .persistent()
+            .remove::<Storage>(&offer_sell_glyph_spender_key);
+    }
+}
+
+// remove one of a glyph's sell offers, returning whether it was listed
+fn remove_offer_sell_glyph_buy(env: &Env, sell: u32, buy: &OfferBuy) -> bool {
+    let offer_sell_glyph_key = Storage::OfferSellGlyph(sell);
+    let mut offers = env
+        .storage()
+        .persistent()
+        .get::<Storage, Vec<OfferBuy>>(&offer_sell_glyph_key)
+        .unwrap_or(Vec::new(env));
+
+    let Ok(index) = offers.binary_search(buy) else {
+        return false;
+    };
+
+    offers.remove(index);
+
+    env.storage()
+        .persistent()
+        .set::<Storage, Vec<OfferBuy>>(&offer_sell_glyph_key, &offers);
+
+    if let OfferBuy::Glyph(buy) = buy {
+        remove_offer_buy_glyph(env, sell, *buy);
+    }
+
+    remove_offer_sell_glyph_spender(env, sell, buy);
+
+    true
+}
+
+fn remove_offer_sell_glyph_spender(env: &Env, sell: u32, buy: &OfferBuy) {
+    let offer_sell_glyph_spender_key = Storage::OfferSellGlyphSpender(sell);
+
+    if let Some(mut spenders) = env
+        .storage()
+        .persistent()
+        .get::<Storage, Map<OfferBuy, Address>>(&offer_sell_glyph_spender_key)
+    {
+        if spenders.contains_key(buy.clone()) {
+            spenders.remove(buy.clone());
+
+            if spenders.is_empty() {
+                env.storage()
+                    .persistent()
+                    .remove::<Storage>(&offer_sell_glyph_spender_key);
+            } else {
+                env.storage()
+                    .persistent()
+                    .set::<Storage, Map<OfferBuy, Address>>(
+                        &offer_sell_glyph_spender_key,
+                        &spenders,
+                    );
+            }
+        }
+    }
+}
+
+// a listing made by a spender only stands while they're still approved, a lapsed one is
+// removed instead of filled
+fn check_offer_sell_glyph_spender(env: &Env, sell: u32, buy: &OfferBuy) -> bool {
+    let Some(spender) = env
+        .storage()
+        .persistent()
+        .get::<Storage, Map<OfferBuy, Address>>(&Storage::OfferSellGlyphSpender(sell))
+        .and_then(|spenders| spenders.get(buy.clone()))
+    else {
+        return true;
+    };
+
+    let approved = env
+        .storage()
+        .persistent()
+        .get::<Storage, Address>(&Storage::GlyphOwner(sell))
+        .is_some_and(|glyph_owner| is_glyph_approved(env, &glyph_owner, &spender, sell));
+
+    if !approved {
+        remove_offer_sell_glyph_buy(env, sell, buy);
+    }
+
+    approved
+}
+
+fn transfer_color(
+    env: &Env,
+    spender: &Option<Address>,
+    color: u32,
+    to: Address,
+) -> Result<(), Error> {
+    let color_owner_key = Storage::ColorOwner(color);
+
+    let color_owner = env
+        .storage()
+        .persistent()
+        .get::<Storage, Address>(&color_owner_key)
+        .ok_or(Error::ColorNotClaimed)?;
+
+    require_color_spender(env, &color_owner, spender, color)?;
+
+    env.storage().persistent().set(&color_owner_key, &to);
+
+    // approvals don't carry over to the new owner
+    env.storage()
+        .persistent()
+        .remove::<Storage>(&Storage::ColorApproval(color));
+
+    env.events()
+        .publish((Symbol::new(env, "color_owner_transfer"), to), color);
+
+    Ok(())
+}
+
+fn transfer_glyph(
+    env: &Env,
+    spender: &Option<Address>,
+    glyph_index: u32,
+    to: Address,
+) -> Result<(), Error> {
+    let glyph_owner_key = Storage::GlyphOwner(glyph_index);
+
+    let glyph_owner = env
+        .storage()
+        .persistent()
+        .get::<Storage, Address>(&glyph_owner_key)
+        .ok_or(Error::GlyphNotMinted)?;
+
+    require_glyph_spender(env, &glyph_owner, spender, glyph_index)?;
+
+    set_glyph_owner(env, glyph_index, Some(&glyph_owner), Some(&to));
+
+    env.events()
+        .publish((Symbol::new(env, "glyph_owner_transfer"), to), glyph_index);
+
+    Ok(())
+}
+
+fn offer_glyph(
+    env: &Env,
+    spender: &Option<Address>,
+    sell: u32,
+    buy: OfferBuy,
+) -> Result<Option<Address>, Error> {
+    let glyph_owner_key = Storage::GlyphOwner(sell);
+    let offer_sell_glyph_key = Storage::OfferSellGlyph(sell);
+
+    let glyph_owner = env
+        .storage()
+        .persistent()
+        .get::<Storage, Address>(&glyph_owner_key)
+        .ok_or(Error::GlyphNotMinted)?;
+
+    require_glyph_spender(env, &glyph_owner, spender, sell)?;
+
+    match &buy {
+        OfferBuy::Glyph(buy) => {
+            let offer_buy_glyph_key = Storage::OfferSellGlyph(*buy);
+
+            let offers = env
+                .storage()
+                .persistent()
+                .get::<Storage, Vec<OfferBuy>>(&offer_buy_glyph_key)
+                .unwrap_or(Vec::new(env));
+
+            match offers.binary_search(OfferBuy::Glyph(sell)) {
+                // Found a matching offer
+                Ok(_index) if check_offer_sell_glyph_spender(env, *buy, &OfferBuy::Glyph(sell)) => {
+                    let buy_glyph_owner_key = Storage::GlyphOwner(*buy);
+                    let buy_glyph_owner = env
+                        .storage()
+                        .persistent()
+                        .get::<Storage, Address>(&buy_glyph_owner_key)
+                        .ok_or(Error::GlyphNotMinted)?;
+
+                    // swap glyph ownership, which removes all open sell offers of both glyphs
+                    set_glyph_owner(env, sell, Some(&glyph_owner), Some(&buy_glyph_owner));
+                    set_glyph_owner(env, *buy, Some(&buy_glyph_owner), Some(&glyph_owner));
+
+                    env.events().publish(
+                        (
+                            Symbol::new(env, "offer_sell_glyph"),
+                            sell,
+                            buy.clone(),
+                            glyph_owner,
+                        ),
+                        Some(&buy_glyph_owner),
+                    ); // ensure we're sending along both swapped glyph owners
+
+                    return Ok(Some(buy_glyph_owner));
+                }
+                // No matching offer found
+                _ => {}
+            }
+        }
+        OfferBuy::Asset(buy, amount) => {
+            check_asset(env, buy, amount)?;
+            check_sale_amount(env, sell, amount)?;
+
+            let offer_sell_asset_key = Storage::OfferSellAsset(sell.clone(), buy.clone(), *amount);
+
+            let mut offers = env
+                .storage()
+                .persistent()
+                .get::<Storage, Vec<Address>>(&offer_sell_asset_key)
+                .unwrap_or(Vec::new(env));
+
+            match offers.get(0) {
+                // Found a matching offer
+                Some(owner) => {
+                    let offer_sell_asset_escrow_key = Storage::OfferSellAssetEscrow(
+                        sell.clone(),
+                        buy.clone(),
+                        *amount,
+                        owner.clone(),
+                    );
+                    let escrowed_amount = env
+                        .storage()
+                        .persistent()
+                        .get::<Storage, i128>(&offer_sell_asset_escrow_key)
+                        .unwrap_or(*amount);
+
+                    env.storage()
+                        .persistent()
+                        .remove::<Storage>(&offer_sell_asset_escrow_key);
+
+                    settle_sale(env, sell, &glyph_owner, &buy, &escrowed_amount)?;
+
+                    // swap glyph ownership, which removes all open buy glyph sell offers
+                    set_glyph_owner(env, sell, Some(&glyph_owner), Some(&owner));
+
+                    // remove and update offers
+                    offers.remove(0);
+
+                    env.storage()
+                        .persistent()
+                        .set::<Storage, Vec<Address>>(&offer_sell_asset_key, &offers);
+
+                    remove_offer_sell_asset_bid(
+                        env,
+                        sell,
+                        &OfferSellAsset(owner.clone(), buy.clone(), *amount),
+                    );
+
+                    env.events().publish(
+                        (Symbol::new(env, "offer_sell_glyph"), sell, buy, *amount),
+                        Some(&owner),
+                    );
+
+                    return Ok(Some(owner));
+                }
+                // No matching offer found
+                None => {}
+            }
+        }
+    }
+
+    let mut offers = env
+        .storage()
+        .persistent()
+        .get::<Storage, Vec<OfferBuy>>(&offer_sell_glyph_key)
+        .unwrap_or(Vec::new(env));
+
+    match offers.binary_search(buy.clone()) {
+        Ok(_index) => {
+            return Err(Error::OfferDuplicate);
+        }
+        Err(index) => offers.insert(index, buy.clone()),
+    }
+
+    if let OfferBuy::Glyph(buy) = &buy {
+        add_offer_buy_glyph(env, sell, *buy)?;
+    }
+
+    env.storage()
+        .persistent()
+        .set::<Storage, Vec<OfferBuy>>(&offer_sell_glyph_key, &offers);
+
+    // remember who listed so the listing lapses with their approval
+    if let Some(spender) = spender {
+        if *spender != glyph_owner {
+            let offer_sell_glyph_spender_key = Storage::OfferSellGlyphSpender(sell);
+            let mut spenders = env
+                .storage()
+                .persistent()
+                .get::<Storage, Map<OfferBuy, Address>>(&offer_sell_glyph_spender_key)
+                .unwrap_or(Map::new(env));
+
+            spenders.set(buy.clone(), spender.clone());
+
+            env.storage()
+                .persistent()
+                .set::<Storage, Map<OfferBuy, Address>>(&offer_sell_glyph_spender_key, &spenders);
+        }
+    }
+
+    env.events().publish(
+        (Symbol::new(env, "offer_sell_glyph"), sell, buy),
+        None::<()>,
+    );
+
+    Ok(None)
+}
+
+fn remove_glyph_offer(
+    env: &Env,
+    spender: &Option<Address>,
+    sell: u32,
+    buy: Option<OfferBuy>,
+) -> Result<(), Error> {
+    let glyph_owner_key = Storage::GlyphOwner(sell);
+
+    let glyph_owner = env
+        .storage()
+        .persistent()
+        .get::<Storage, Address>(&glyph_owner_key)
+        .ok_or(Error::GlyphNotMinted)?;
+
+    require_glyph_spender(env, &glyph_owner, spender, sell)?;
+
+    env.events().publish(
+        (
+            Symbol::new(env, "offer_sell_glyph_remove"),
+            sell,
+            buy.clone(),
+        ),
+        (),
+    );
+
+    match buy {
+        Some(buy) => {
+            if remove_offer_sell_glyph_buy(env, sell, &buy) {
+                Ok(())
+            } else {
+                Err(Error::OfferNotFound)
+            }
+        }
+        None => {
+            remove_offer_sell_glyph(env, sell);
+
+            Ok(())
+        }
+    }
+}
+
+// everything a mint entry point stores for a new glyph
+struct GlyphMint {
+    owner: Address,
+    glyph_index: u32,
+    glyph: Glyph,
+    glyph_hash: BytesN<32>,
+    glyph_palette: GlyphPalette,
+    author_split: Option<RoyaltySplit>,
+    metadata: GlyphMetadata,
+}
+
+fn store_glyph(env: &Env, source: &Address, glyph_mint: GlyphMint) -> Result<(), Error> {
+    let GlyphMint {
+        owner,
+        glyph_index,
+        glyph,
+        glyph_hash,
+        glyph_palette,
+        author_split,
+        metadata,
+    } = glyph_mint;
+
+    env.storage().persistent().set::<Storage, u32>(
+        &Storage::GlyphIndexHashMap(glyph_hash.clone()),
+        &glyph_index,
+    );
+
+    // kept so a burn can find the hash again without re-rendering the glyph
+    env.storage()
+        .persistent()
+        .set::<Storage, BytesN<32>>(&Storage::GlyphHash(glyph_index), &glyph_hash);
+
+    env.storage()
+        .persistent()
+        .set::<Storage, Glyph>(&Storage::Glyph(glyph_index), &glyph);
+
+    env.storage()
+        .persistent()
+        .set::<Storage, GlyphPalette>(&Storage::GlyphPalette(glyph_index), &glyph_palette);
+
+    set_glyph_owner(env, glyph_index, None, Some(&owner));
+
+    if let Some(author_split) = author_split {
+        env.storage()
+            .persistent()
+            .set::<Storage, RoyaltySplit>(&Storage::GlyphAuthorSplit(glyph_index), &author_split);
+    }
+
+    // kept apart from the glyph so the hashed pixel data never changes with its metadata
+    env.storage()
+        .persistent()
+        .set::<Storage, GlyphMetadata>(&Storage::GlyphMetadata(glyph_index), &metadata);
+
+    pay_mine_fees(env, source, glyph_index)?;
+
+    env.events().publish(
+        (Symbol::new(env, "glyph_mint"), glyph.author, owner),
+        (glyph_index, metadata.title, metadata.story, glyph.edition),
+    );
+
+    Ok(())
+}
+
+// every glyph ownership change goes through here to keep balances in step and drop the
+// glyph's approval and listings, from None mints and to None burns
+fn set_glyph_owner(env: &Env, glyph_index: u32, from: Option<&Address>, to: Option<&Address>) {
+    let glyph_owner_key = Storage::GlyphOwner(glyph_index);
+
+    match to {
+        Some(to) => env
+            .storage()
+            .persistent()
+            .set::<Storage, Address>(&glyph_owner_key, to),
+        None => env
+            .storage()
+            .persistent()
+            .remove::<Storage>(&glyph_owner_key),
+    }
+
+    env.storage()
+        .persistent()
+        .remove::<Storage>(&Storage::GlyphApproval(glyph_index));
+
+    // the previous owner's listings don't carry over to the new owner
+    if from.is_some() {
+        remove_offer_sell_glyph(env, glyph_index);
+    }
+
+    // glyphs minted before balances were tracked are left to glyph_balances_migrate
+    let counted = glyph_index >= get_glyph_balance_index(env);
+
+    if let (Some(from), true) = (from, counted) {
+        let balance_key = Storage::GlyphBalance(from.clone());
+        let balance = env
+            .storage()
+            .persistent()
+            .get::<Storage, u32>(&balance_key)
+            .unwrap_or(0);
+
+        env.storage()
+            .persistent()
+            .set::<Storage, u32>(&balance_key, &(balance - 1));
+    }
+
+    if let (Some(to), true) = (to, counted) {
+        let balance_key = Storage::GlyphBalance(to.clone());
+        let balance = env
+            .storage()
+            .persistent()
+            .get::<Storage, u32>(&balance_key)
+            .unwrap_or(0);
+
+        env.storage()
+            .persistent()
+            .set::<Storage, u32>(&balance_key, &(balance + 1));
+    }
+
+    // the standard NFT events, alongside the glyph specific ones
+    match (from, to) {
+        (Some(from), Some(to)) => env.events().publish(
+            (Symbol::new(env, "transfer"), from.clone(), to.clone()),
+            glyph_index,
+        ),
+        (None, Some(to)) => env
+            .events()
+            .publish((Symbol::new(env, "mint"), to.clone()), glyph_index),
+        (Some(from), None) => env
+            .events()
+            .publish((Symbol::new(env, "burn"), from.clone()), glyph_index),
+        (None, None) => {}
+    }
+}
+
+fn is_operator_approved(env: &Env, owner: &Address, operator: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .get::<Storage, u32>(&Storage::Operator(owner.clone(), operator.clone()))
+        .is_some_and(|live_until_ledger| live_until_ledger >= env.ledger().sequence())
+}
+
+fn is_glyph_approved(env: &Env, owner: &Address, spender: &Address, glyph_index: u32) -> bool {
+    spender == owner
+        || is_operator_approved(env, owner, spender)
+        || get_approval(env, &Storage::GlyphApproval(glyph_index))
+            .is_some_and(|(approved, _)| approved == *spender)
+}
+
+fn is_color_approved(env: &Env, owner: &Address, spender: &Address, color: u32) -> bool {
+    spender == owner
+        || is_operator_approved(env, owner, spender)
+        || get_approval(env, &Storage::ColorApproval(color))
+            .is_some_and(|(approved, _)| approved == *spender)
+}
+
+// the owner signs unless an approved spender is named, who signs instead
+fn require_glyph_spender(
+    env: &Env,
+    owner: &Address,
+    spender: &Option<Address>,
+    glyph_index: u32,
+) -> Result<(), Error> {
+    match spender {
+        Some(spender) => {
+            spender.require_auth();
+
+            if !is_glyph_approved(env, owner, spender, glyph_index) {
+                return Err(Error::ApprovalInsufficient);
+            }
+        }
+        None => owner.require_auth(),
+    }
+
+    Ok(())
+}
+
+fn require_color_spender(
+    env: &Env,
+    owner: &Address,
+    spender: &Option<Address>,
+    color: u32,
+) -> Result<(), Error> {
+    match spender {
+        Some(spender) => {
+            spender.require_auth();
+
+            if !is_color_approved(env, owner, spender, color) {
+                return Err(Error::ApprovalInsufficient);
+            }
+        }
+        None => owner.require_auth(),
+    }
+
+    Ok(())
+}
+
+// the approved address and its expiration, unless it has expired
+fn get_approval(env: &Env, approval_key: &Storage) -> Option<(Address, u32)> {
+    env.storage()
+        .persistent()
+        .get::<Storage, (Address, u32)>(approval_key)
+        .filter(|(_, live_until_ledger)| *live_until_ledger >= env.ledger().sequence())
+}
+
+// a live_until_ledger of 0 revokes, otherwise it can't already have passed
+fn set_approval(
+    env: &Env,
+    approval_key: &Storage,
+    approved: &Address,
+    live_until_ledger: u32,
+) -> Result<(), Error> {
+    if live_until_ledger == 0 {
+        env.storage().persistent().remove::<Storage>(approval_key);
+    } else if live_until_ledger < env.ledger().sequence() {
+        return Err(Error::ApprovalExpirationInvalid);
+    } else {
+        env.storage()
+            .persistent()
+            .set::<Storage, (Address, u32)>(approval_key, &(approved.clone(), live_until_ledger));
+    }
+
+    Ok(())
+}
+
+fn pay_mine_fees(env: &Env, source: &Address, glyph_index: u32) -> Result<(), Error> {
+    pay_fee(env, Storage::GlyphMineFee, source)?;
+
+    // pay the color owners for the pixels this glyph uses
+    let glyph_mine_color_fee = env
+        .storage()
+        .instance()
+        .get::<Storage, i128>(&Storage::GlyphMineColorFee)
+        .unwrap_or(0);
+
+    if glyph_mine_color_fee > 0 {
+        let fee_sac = env
+            .storage()
+            .instance()
+            .get::<Storage, Address>(&Storage::FeeSAC)
+            .ok_or(Error::NotInitialized)?;
+
+        let glyph_palette = get_stored_glyph_palette(env, glyph_index)?;
+
+        // unclaimed colors earn nothing so the minter is only charged what gets paid out
+        let color_shares = get_color_shares(env, &glyph_palette, &glyph_mine_color_fee)?;
+
+        if color_shares.total > 0 {
+            receive_asset(env, &fee_sac, source, &color_shares.total)?;
+
+            accrue_color_shares(env, glyph_index, &fee_sac, &color_shares);
+        }
+    }
+
+    Ok(())
+}
+
+// glyphs stored before the upgrade decode as GlyphV1 and take the rates sales settled at then
+fn get_glyph(env: &Env, glyph_index: u32) -> Option<Glyph> {
+    let glyph = env
+        .storage()
+        .persistent()
+        .get::<Storage, Map<Symbol, Val>>(&Storage::Glyph(glyph_index))?;
+
+    // unpacking a struct from a map of another size traps, so check the fields first
+    if glyph.len() > 4 {
+        return Glyph::try_from_val(env, glyph.as_val()).ok();
+    }
+
+    let GlyphV1 {
+        author,
+        colors,
+        legend,
+        width,
+    } = GlyphV1::try_from_val(env, glyph.as_val()).ok()?;
+
+    Some(Glyph {
+        author,
+        colors,
+        legend,
+        width,
+        author_royalty_rate: env
+            .storage()
+            .instance()
+            .get::<Storage, i128>(&Storage::GlyphAuthorRoyaltyRate)
+            .unwrap_or(0),
+        color_owner_royalty_rate: env
+            .storage()
+            .instance()
+            .get::<Storage, i128>(&Storage::ColorOwnerRoyaltyRate)
+            .unwrap_or(0),
+        encoding: GlyphEncoding::Raw,
+        edition: (1, 1),
+    })
+}
+
+// editions share the first edition's metadata, author split and lineage
+fn get_artwork_index(env: &Env, glyph_index: u32) -> Result<u32, Error> {
+    match get_glyph(env, glyph_index) {
+        Some(Glyph {
+            encoding: GlyphEncoding::Edition(first_edition),
+            ..
+        }) => Ok(first_edition),
+        Some(_) => Ok(glyph_index),
+        None => Err(Error::GlyphNotMinted),
+    }
+}
+
+// reject anything which could render the same glyph under a different hash
+fn check_glyph(colors: &Bytes, legend: &Vec<u32>, width: u32) -> Result<(), Error> {
+    check_glyph_size(colors, width)?;
+    check_legend(legend)?;
+    check_glyph_palette(&get_palette(colors.clone(), &GlyphEncoding::Raw), legend)
+}
+
+fn check_glyph_size(colors: &Bytes, width: u32) -> Result<(), Error> {
+    if colors.is_empty() {
+        return Err(Error::GlyphEmpty);
+    }
+
+    if width == 0 || width > MAX_GLYPH_WIDTH {
+        return Err(Error::GlyphWidthInvalid);
+    }
+
+    if !colors.len().is_multiple_of(width) {
+        return Err(Error::GlyphNotRectangular);
+    }
+
+    if colors.len() / width > MAX_GLYPH_WIDTH {
+        return Err(Error::GlyphTooBig);
+    }
+
+    Ok(())
+}
+
+fn check_glyph_palette(palette: &[u32; 256], legend: &Vec<u32>) -> Result<(), Error> {
+    for (index, count) in palette.iter().enumerate() {
+        let in_legend = index < legend.len() as usize;
+
+        if *count > 0 && !in_legend {
+            return Err(Error::ColorNotInLegend);
+        }
+
+        if *count == 0 && in_legend {
+            return Err(Error::LegendColorUnused);
+        }
+    }
+
+    Ok(())
+}
+
+fn check_legend(legend: &Vec<u32>) -> Result<(), Error> {
+    if legend.len() > 256 {
+        return Err(Error::LegendTooLong);
+    }
+
+    // ensure legend is sorted to prevent different hashes for the same glyph
+    for (index, color) in legend.iter().enumerate() {
+        let index = index as u32;
+
+        if color > MAX_COLOR && color != TRANSPARENT_COLOR {
+            return Err(Error::ColorOutOfRange);
+        }
+
+        if index > 0 {
+            let previous_color = legend.get_unchecked(index - 1);
+
+            if color == previous_color {
+                return Err(Error::LegendDuplicate);
+            }
+
+            if color < previous_color {
+                return Err(Error::LegendUnordered);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// compact the legend to its used colors in ascending order and renumber the pixels to match,
+// so every encoding of the same image ends up with the same colors and legend
+fn canonicalize_glyph(
+    env: &Env,
+    colors: &Bytes,
+    legend: &Vec<u32>,
+) -> Result<(Bytes, Vec<u32>), Error> {
+    if legend.len() > 256 {
+        return Err(Error::LegendTooLong);
+    }
+
+    let mut used = [(0u32, 0u8); 256]; // (u32 color, u8 index)
+    let mut used_length = 0;
+
+    for (index, count) in get_palette(colors.clone(), &GlyphEncoding::Raw)
+        .into_iter()
+        .enumerate()
+    {
+        if count == 0 {
+            continue;
+        }
+
+        if index >= legend.len() as usize {
+            return Err(Error::ColorNotInLegend);
+        }
+
+        used[used_length] = (legend.get_unchecked(index as u32), index as u8);
+        used_length += 1;
+    }
+
+    used[..used_length].sort_unstable();
+
+    let mut indexes = [0u8; 256];
+    let mut canonical_legend = Vec::new(env);
+
+    // duplicate legend entries collapse into one color
+    for (color, index) in used[..used_length].iter() {
+        if canonical_legend.last() != Some(*color) {
+            canonical_legend.push_back(*color);
+        }
+
+        indexes[*index as usize] = (canonical_legend.len() - 1) as u8;
+    }
+
+    let mut canonical_colors = Bytes::new(env);
+
+    for color in colors.iter() {
+        canonical_colors.push_back(indexes[color as usize]);
+    }
+
+    Ok((canonical_colors, canonical_legend))
+}
+
+// the glyph's hash, as long as neither it nor a symmetry the policy rejects is already minted
+fn check_glyph_duplicate(
+    env: &Env,
+    colors: &Bytes,
+    legend: &Vec<u32>,
+    width: u32,
+) -> Result<BytesN<32>, Error> {
+    let glyph_hash = get_glyph_hash(env, colors, legend, width);
+
+    if env
+        .storage()
+        .persistent()
+        .has::<Storage>(&Storage::GlyphIndexHashMap(glyph_hash.clone()))
+    {
+        return Err(Error::GlyphAlreadyMinted);
+    }
+
+    // optionally reject flipped or rotated copies of existing glyphs
+    let glyph_symmetry_policy = env
+        .storage()
+        .instance()
+        .get::<Storage, u32>(&Storage::GlyphSymmetryPolicy)
+        .unwrap_or(0);
+
+    for (symmetry_colors, symmetry_width) in
+        get_glyph_symmetries(env, colors, width, glyph_symmetry_policy).iter()
+    {
+        let symmetry_hash = get_glyph_hash(env, &symmetry_colors, legend, symmetry_width);
+
+        if env
+            .storage()
+            .persistent()
+            .has::<Storage>(&Storage::GlyphIndexHashMap(symmetry_hash))
+        {
+            return Err(Error::GlyphSymmetricDuplicate);
+        }
+    }
+
+    Ok(glyph_hash)
+}
+
+// like check_glyph_duplicate, with every frame flipped or rotated the same way
+fn check_animation_duplicate(
+    env: &Env,
+    frames: &Vec<Bytes>,
+    legend: &Vec<u32>,
+    width: u32,
+) -> Result<BytesN<32>, Error> {
+    let glyph_hash = get_animation_hash(env, frames, legend, width);
+
+    if env
+        .storage()
+        .persistent()
+        .has::<Storage>(&Storage::GlyphIndexHashMap(glyph_hash.clone()))
+    {
+        return Err(Error::GlyphAlreadyMinted);
+    }
+
+    let glyph_symmetry_policy = env
+        .storage()
+        .instance()
+        .get::<Storage, u32>(&Storage::GlyphSymmetryPolicy)
+        .unwrap_or(0);
+
+    // each frame's transforms come back in the same order
+    let mut frame_symmetries = Vec::new(env);
+
+    for frame in frames.iter() {
+        frame_symmetries.push_back(get_glyph_symmetries(
+            env,
+            &frame,
+            width,
+            glyph_symmetry_policy,
+        ));
+    }
+
+    let transforms = frame_symmetries.get_unchecked(0).len();
+
+    for transform in 0..transforms {
+        let mut symmetry_frames = Vec::new(env);
+        let mut symmetry_width = width;
+
+        for symmetries in frame_symmetries.iter() {
+            let (symmetry_colors, transformed_width) = symmetries.get_unchecked(transform);
+
+            symmetry_frames.push_back(symmetry_colors);
+            symmetry_width = transformed_width;
+        }
+
+        let symmetry_hash = get_animation_hash(env, &symmetry_frames, legend, symmetry_width);
+
+        if env
+            .storage()
+            .persistent()
+            .has::<Storage>(&Storage::GlyphIndexHashMap(symmetry_hash))
+        {
+            return Err(Error::GlyphSymmetricDuplicate);
+        }
+    }
+
+    Ok(glyph_hash)
+}
+
+// hashed over every frame and the frame count, so it can't collide with a taller glyph
+fn get_animation_hash(env: &Env, frames: &Vec<Bytes>, legend: &Vec<u32>, width: u32) -> BytesN<32> {
+    let mut colors = Bytes::new(env);
+
+    for frame in frames.iter() {
+        colors.append(&frame);
+    }
+
+    colors.append(&legend.clone().to_xdr(env));
+    colors.extend_from_slice(&width.to_be_bytes());
+    colors.extend_from_slice(&frames.len().to_be_bytes());
+
+    env.crypto().sha256(&colors).to_bytes()
+}
+
+// ensure the hash includes colors, legend and width
+fn get_glyph_hash(env: &Env, colors: &Bytes, legend: &Vec<u32>, width: u32) -> BytesN<32> {
+    let mut colors_extended_with_width = colors.clone();
+    let legend_xdr = legend.clone().to_xdr(env);
+
+    colors_extended_with_width.append(&legend_xdr);
+    colors_extended_with_width.extend_from_slice(&width.to_be_bytes());
+
+    env.crypto().sha256(&colors_extended_with_width).to_bytes()
+}
+
+// the glyph's pixels under each transform enabled by `policy`, with the transformed width
+fn get_glyph_symmetries(env: &Env, colors: &Bytes, width: u32, policy: u32) -> Vec<(Bytes, u32)> {
+    // only tiled canvases need the larger buffers
+    if colors.len() <= MAX_GLYPH_WIDTH * MAX_GLYPH_WIDTH {
+        transform_glyph::<{ (MAX_GLYPH_WIDTH * MAX_GLYPH_WIDTH) as usize }>(
+            env, colors, width, policy,
+        )
+    } else {
+        transform_glyph::<{ (MAX_GLYPH_TILED_WIDTH * MAX_GLYPH_TILED_WIDTH) as usize }>(
+            env, colors, width, policy,
+        )
+    }
+}
+
+fn transform_glyph<const PIXELS: usize>(
+    env: &Env,
+    colors: &Bytes,
+    width: u32,
+    policy: u32,
+) -> Vec<(Bytes, u32)> {
+    let mut symmetries = Vec::new(env);
+
+    if policy == 0 {
+        return symmetries;
+    }
+
+    let length = colors.len() as usize;
+    let width = width as usize;
+    let height = length / width;
+
+    let mut pixels = [0u8; PIXELS];
+
+    colors.copy_into_slice(&mut pixels[..length]);
+
+    let mut transforms: [(u32, usize); 5] = [(0, 0); 5]; // (transform, transformed width)
+    let mut transforms_length = 0;
+
+    if policy & SYMMETRY_FLIP_HORIZONTAL != 0 {
+        transforms[transforms_length] = (SYMMETRY_FLIP_HORIZONTAL, width);
+        transforms_length += 1;
+    }
+
+    if policy & SYMMETRY_FLIP_VERTICAL != 0 {
+        transforms[transforms_length] = (SYMMETRY_FLIP_VERTICAL, width);
+        transforms_length += 1;
+    }
+
+    if policy & SYMMETRY_ROTATE != 0 {
+        transforms[transforms_length] = (90, height);
+        transforms[transforms_length + 1] = (180, width);
+        transforms[transforms_length + 2] = (270, height);
+        transforms_length += 3;
+    }
+
+    for (transform, transformed_width) in transforms[..transforms_length].iter() {
+        let mut transformed = [0u8; PIXELS];
+
+        for y in 0..height {
+            for x in 0..width {
+                let pixel = pixels[y * width + x];
+                let index = match *transform {
+                    SYMMETRY_FLIP_HORIZONTAL => y * width + (width - 1 - x),
+                    SYMMETRY_FLIP_VERTICAL => (height - 1 - y) * width + x,
+                    90 => x * height + (height - 1 - y),
+                    180 => (height - 1 - y) * width + (width - 1 - x),
+                    _ => (width - 1 - x) * height + y,
+                };
+
+                transformed[index] = pixel;
+            }
+        }
+
+        symmetries.push_back((
+            Bytes::from_slice(env, &transformed[..length]),
+            *transformed_width as u32,
+        ));
+    }
+
+    symmetries
+}
+
+fn check_glyph_metadata(title: &String, story: &String) -> Result<(), Error> {
+    if title.len() > MAX_GLYPH_TITLE_LENGTH {
+        return Err(Error::GlyphTitleTooLong);
+    }
+
+    if story.len() > MAX_GLYPH_STORY_LENGTH {
+        return Err(Error::GlyphStoryTooLong);
+    }
 
-                match offers.binary_search(OfferBuy::Glyph(sell)) {
-                    // Found a matching offer
-                    Ok(_index) => {
-                        let buy_glyph_owner_key = Storage::GlyphOwner(*buy);
-                        let buy_glyph_owner = env
-                            .storage()
-                            .persistent()
-                            .get::<Storage, Address>(&buy_glyph_owner_key)
-                            .ok_or(Error::GlyphNotMinted)?;
+    Ok(())
+}
 
-                        // swap glyph ownership
-                        env.storage()
-                            .persistent()
-                            .set::<Storage, Address>(&glyph_owner_key, &buy_glyph_owner);
-                        env.storage()
-                            .persistent()
-                            .set::<Storage, Address>(&buy_glyph_owner_key, &glyph_owner);
+// rates are percentages and the seller's proceeds can never go negative
+fn check_royalty_rates(
+    color_owner_royalty_rate: i128,
+    glyph_author_royalty_rate: i128,
+) -> Result<(), Error> {
+    if color_owner_royalty_rate < 0
+        || glyph_author_royalty_rate < 0
+        || color_owner_royalty_rate + glyph_author_royalty_rate > 100
+    {
+        return Err(Error::RoyaltyRateOutOfRange);
+    }
 
-                        // remove all open buy glyph sell offers
-                        if env
-                            .storage()
-                            .persistent()
-                            .has::<Storage>(&offer_buy_glyph_key)
-                        {
-                            env.storage()
-                                .persistent()
-                                .remove::<Storage>(&offer_buy_glyph_key);
-                        }
+    Ok(())
+}
 
-                        // remove all open sell glyph sell offers
-                        if env
-                            .storage()
-                            .persistent()
-                            .has::<Storage>(&offer_sell_glyph_key)
-                        {
-                            env.storage()
-                                .persistent()
-                                .remove::<Storage>(&offer_sell_glyph_key);
-                        }
+// sales too small to pay every non-zero royalty at least 1 stroop are rejected
+fn check_sale_amount(env: &Env, glyph_index: u32, amount: &i128) -> Result<(), Error> {
+    let Glyph {
+        author_royalty_rate,
+        color_owner_royalty_rate,
+        ..
+    } = get_glyph(env, glyph_index).ok_or(Error::GlyphNotMinted)?;
+
+    for royalty_rate in [author_royalty_rate, color_owner_royalty_rate] {
+        if royalty_rate > 0 && royalty_rate.fixed_mul_floor(env, amount, &100) < 1 {
+            return Err(Error::SaleAmountTooLow);
+        }
+    }
 
-                        env.events().publish(
-                            (
-                                Symbol::new(&env, "offer_sell_glyph"),
-                                sell,
-                                buy.clone(),
-                                glyph_owner,
-                            ),
-                            Some(&buy_glyph_owner),
-                        ); // ensure we're sending along both swapped glyph owners
-
-                        return Ok(Some(buy_glyph_owner));
-                    }
-                    // No matching offer found
-                    Err(_index) => {}
+    Ok(())
+}
+
+fn get_palette(colors: Bytes, encoding: &GlyphEncoding) -> [u32; 256] {
+    let colors_length = colors.len() as usize;
+
+    // let mut colors_bytes = [0u8; 8192];
+    let mut palette_bytes = [0u32; 256];
+
+    // colors.copy_into_slice(&mut colors_bytes);
+    // TODO should we loop over colors or colors_bytes?
+
+    match encoding {
+        GlyphEncoding::Raw => {
+            for (index, color) in colors.into_iter().enumerate() {
+                if index > colors_length {
+                    break;
                 }
+
+                palette_bytes[color as usize] += 1;
+            }
+        }
+        GlyphEncoding::Packed(bits, pixels) => {
+            for index in 0..*pixels {
+                palette_bytes[get_packed_color(&colors, *bits, index) as usize] += 1;
             }
-            OfferBuy::Asset(buy, amount) => {
-                let offer_sell_asset_key =
-                    Storage::OfferSellAsset(sell.clone(), buy.clone(), *amount);
+        }
+        GlyphEncoding::Tiled(_)
+        | GlyphEncoding::Delta
+        | GlyphEncoding::Recolor(_)
+        | GlyphEncoding::Composite(..)
+        | GlyphEncoding::Edition(_) => {}
+        GlyphEncoding::RunLength => {
+            for index in (0..colors.len()).step_by(2) {
+                palette_bytes[colors.get_unchecked(index + 1) as usize] +=
+                    colors.get_unchecked(index) as u32;
+            }
+        }
+    }
 
-                let mut offers = env
-                    .storage()
-                    .persistent()
-                    .get::<Storage, Vec<Address>>(&offer_sell_asset_key)
-                    .unwrap_or(Vec::new(&env));
+    palette_bytes
+}
 
-                match offers.get(0) {
-                    // Found a matching offer
-                    Some(owner) => {
-                        let Glyph {
-                            author,
-                            colors,
-                            legend,
-                            ..
-                        } = env
-                            .storage()
-                            .persistent()
-                            .get::<Storage, Glyph>(&Storage::Glyph(sell.clone()))
-                            .ok_or(Error::GlyphNotMinted)?;
-
-                        // transfer to glyph author
-                        let glyph_author_royalty_rate = env
-                            .storage()
-                            .instance()
-                            .get::<Storage, i128>(&Storage::GlyphAuthorRoyaltyRate)
-                            .ok_or(Error::NotInitialized)?;
-                        let author_amount = glyph_author_royalty_rate
-                            .fixed_mul_floor(&env, &amount, &100)
-                            .max(1);
-
-                        update_royalties(&env, &author, &buy, &author_amount);
-
-                        // transfer to color owners
-                        let colors_length = colors.len() as i128;
-                        let color_owner_royalty_rate = env
-                            .storage()
-                            .instance()
-                            .get::<Storage, i128>(&Storage::ColorOwnerRoyaltyRate)
-                            .ok_or(Error::NotInitialized)?;
-
-                        let legend_length = legend.len() as usize;
-                        let mut color_owner_amounts = 0;
-
-                        // TODO likely need to limit this to the first N ordered by highest count (125 storage gets)
-                        for (index, count) in get_palette(colors).into_iter().enumerate() {
-                            if index >= legend_length {
-                                break;
-                            }
-
-                            let color = legend.get_unchecked(index as u32);
-
-                            match env
-                                .storage()
-                                .persistent()
-                                .get::<Storage, Address>(&Storage::ColorOwner(color))
-                            {
-                                Some(color_owner) => {
-                                    let color_owner_amount = color_owner_royalty_rate
-                                        .fixed_mul_floor(&env, &amount, &100)
-                                        .fixed_mul_floor(&env, &(count as i128), &colors_length)
-                                        .max(1);
-
-                                    update_royalties(&env, &color_owner, &buy, &color_owner_amount);
-
-                                    color_owner_amounts += color_owner_amount;
-                                }
-                                None => continue,
-                            }
-                        }
+fn get_packed_color(colors: &Bytes, bits: u32, index: u32) -> u8 {
+    let offset = index * bits;
+    let byte = colors.get_unchecked(offset / 8);
 
-                        // transfer asset to buy glyph owner
-                        update_royalties(
-                            &env,
-                            &glyph_owner,
-                            &buy,
-                            &(amount - author_amount - color_owner_amounts),
-                        );
+    (byte >> (8 - bits - offset % 8)) & ((1 << bits) - 1) as u8
+}
 
-                        // swap glyph ownership
-                        env.storage()
-                            .persistent()
-                            .set::<Storage, Address>(&glyph_owner_key, &owner);
+// pick the smallest of bit packing (sized by the legend) and run-length encoding
+fn encode_colors(env: &Env, colors: &Bytes, legend_length: u32) -> (Bytes, GlyphEncoding) {
+    let length = colors.len() as usize;
+    let bits: usize = match legend_length {
+        0..=2 => 1,
+        3..=4 => 2,
+        5..=16 => 4,
+        _ => 8,
+    };
 
-                        // remove all open buy glyph sell offers
-                        if env
-                            .storage()
-                            .persistent()
-                            .has::<Storage>(&offer_sell_glyph_key)
-                        {
-                            env.storage()
-                                .persistent()
-                                .remove::<Storage>(&offer_sell_glyph_key);
-                        }
+    let mut pixels = [0u8; (MAX_GLYPH_WIDTH * MAX_GLYPH_WIDTH) as usize];
 
-                        // remove and update offers
-                        offers.remove(0);
+    colors.copy_into_slice(&mut pixels[..length]);
 
-                        env.storage()
-                            .persistent()
-                            .set::<Storage, Vec<Address>>(&offer_sell_asset_key, &offers);
+    let mut runs_length = 0;
+    let mut index = 0;
 
-                        env.events().publish(
-                            (Symbol::new(&env, "offer_sell_glyph"), sell, buy, *amount),
-                            Some(&owner),
-                        );
+    while index < length {
+        let mut run = 1;
 
-                        return Ok(Some(owner));
-                    }
-                    // No matching offer found
-                    None => {}
-                }
+        while index + run < length && run < 255 && pixels[index + run] == pixels[index] {
+            run += 1;
+        }
+
+        runs_length += 2;
+        index += run;
+    }
+
+    let packed_length = (length * bits).div_ceil(8);
+
+    let mut encoded = [0u8; (MAX_GLYPH_WIDTH * MAX_GLYPH_WIDTH) as usize];
+
+    if runs_length < packed_length {
+        let mut encoded_length = 0;
+        let mut index = 0;
+
+        while index < length {
+            let mut run = 1;
+
+            while index + run < length && run < 255 && pixels[index + run] == pixels[index] {
+                run += 1;
             }
+
+            encoded[encoded_length] = run as u8;
+            encoded[encoded_length + 1] = pixels[index];
+            encoded_length += 2;
+            index += run;
         }
 
-        let mut offers = env
-            .storage()
-            .persistent()
-            .get::<Storage, Vec<OfferBuy>>(&offer_sell_glyph_key)
-            .unwrap_or(Vec::new(&env));
+        (
+            Bytes::from_slice(env, &encoded[..encoded_length]),
+            GlyphEncoding::RunLength,
+        )
+    } else if bits < 8 {
+        for (index, color) in pixels[..length].iter().enumerate() {
+            let offset = index * bits;
+
+            encoded[offset / 8] |= color << (8 - bits - offset % 8);
+        }
+
+        (
+            Bytes::from_slice(env, &encoded[..packed_length]),
+            GlyphEncoding::Packed(bits as u32, length as u32),
+        )
+    } else {
+        (colors.clone(), GlyphEncoding::Raw)
+    }
+}
+
+fn encode_delta(env: &Env, previous: &Bytes, colors: &Bytes) -> Bytes {
+    let mut delta = Bytes::new(env);
+
+    for (index, (previous_color, color)) in previous.iter().zip(colors.iter()).enumerate() {
+        if color != previous_color {
+            delta.extend_from_slice(&(index as u16).to_be_bytes());
+            delta.push_back(color);
+        }
+    }
+
+    delta
+}
+
+fn decode_delta(env: &Env, previous: &Bytes, delta: &Bytes) -> Bytes {
+    let mut decoded = [0u8; (MAX_GLYPH_WIDTH * MAX_GLYPH_WIDTH) as usize];
+    let decoded_length = previous.len() as usize;
 
-        match offers.binary_search(buy.clone()) {
-            Ok(_index) => {
-                return Err(Error::OfferDuplicate);
+    previous.copy_into_slice(&mut decoded[..decoded_length]);
+
+    for index in (0..delta.len()).step_by(3) {
+        let pixel =
+            u16::from_be_bytes([delta.get_unchecked(index), delta.get_unchecked(index + 1)]);
+
+        decoded[pixel as usize] = delta.get_unchecked(index + 2);
+    }
+
+    Bytes::from_slice(env, &decoded[..decoded_length])
+}
+
+fn decode_colors(env: &Env, colors: &Bytes, encoding: &GlyphEncoding) -> Bytes {
+    let mut decoded = [0u8; (MAX_GLYPH_WIDTH * MAX_GLYPH_WIDTH) as usize];
+    let mut decoded_length = 0;
+
+    match encoding {
+        GlyphEncoding::Raw | GlyphEncoding::Tiled(_) | GlyphEncoding::Delta => {
+            return colors.clone()
+        }
+        GlyphEncoding::Recolor(parent) | GlyphEncoding::Edition(parent) => {
+            return match get_glyph(env, *parent) {
+                Some(Glyph {
+                    colors, encoding, ..
+                }) => decode_colors(env, &colors, &encoding),
+                None => Bytes::new(env),
             }
-            Err(index) => offers.insert(index, buy.clone()),
         }
+        GlyphEncoding::Composite(width, height) => {
+            return match render_composite(env, &decode_layers(env, colors), *width, *height) {
+                Ok((colors, _)) => colors,
+                Err(_) => Bytes::new(env),
+            }
+        }
+        GlyphEncoding::Packed(bits, pixels) => {
+            for index in 0..*pixels {
+                decoded[index as usize] = get_packed_color(colors, *bits, index);
+            }
 
-        env.storage()
-            .persistent()
-            .set::<Storage, Vec<OfferBuy>>(&offer_sell_glyph_key, &offers);
+            decoded_length = *pixels as usize;
+        }
+        GlyphEncoding::RunLength => {
+            for index in (0..colors.len()).step_by(2) {
+                let run = colors.get_unchecked(index) as usize;
 
-        env.events().publish(
-            (Symbol::new(&env, "offer_sell_glyph"), sell, buy),
-            None::<()>,
-        );
+                decoded[decoded_length..decoded_length + run].fill(colors.get_unchecked(index + 1));
+                decoded_length += run;
+            }
+        }
+    }
+
+    Bytes::from_slice(env, &decoded[..decoded_length])
+}
+
+fn encode_layers(env: &Env, layers: &Vec<(u32, u32, u32, u32)>) -> Bytes {
+    let mut encoded = Bytes::new(env);
 
-        Ok(None)
+    for (glyph_index, x, y, z) in layers.iter() {
+        encoded.extend_from_slice(&glyph_index.to_be_bytes());
+        encoded.extend_from_slice(&x.to_be_bytes());
+        encoded.extend_from_slice(&y.to_be_bytes());
+        encoded.extend_from_slice(&z.to_be_bytes());
     }
-    pub fn offer_sell_asset(
-        env: Env,
-        sell: OfferSellAsset,
-        buy: u32,
-    ) -> Result<Option<Address>, Error> {
-        let OfferSellAsset(owner, sac, amount) = sell.clone();
 
-        owner.require_auth();
+    encoded
+}
 
-        let open_glyph_buy_now_offers_key = Storage::OfferSellGlyph(buy);
-        let open_glyph_buy_now_offers = env
-            .storage()
-            .persistent()
-            .get::<Storage, Vec<OfferBuy>>(&open_glyph_buy_now_offers_key)
-            .unwrap_or(Vec::new(&env));
+fn decode_layers(env: &Env, encoded: &Bytes) -> Vec<(u32, u32, u32, u32)> {
+    let mut layers = Vec::new(env);
+    let mut layer = [0u8; 16];
 
-        match open_glyph_buy_now_offers.binary_search(OfferBuy::Asset(sac.clone(), amount)) {
-            // Found a matching open counter offer. Take it
-            Ok(_index) => {
-                let buy_glyph_owner_key = Storage::GlyphOwner(buy);
-                let buy_glyph_owner = env
-                    .storage()
-                    .persistent()
-                    .get::<Storage, Address>(&buy_glyph_owner_key)
-                    .ok_or(Error::GlyphNotMinted)?;
+    for index in (0..encoded.len()).step_by(16) {
+        encoded.slice(index..index + 16).copy_into_slice(&mut layer);
 
-                // Send the amount to the contract for passive claiming later
-                let token_client = token::TokenClient::new(&env, &sac);
+        let field = |offset: usize| {
+            u32::from_be_bytes([
+                layer[offset],
+                layer[offset + 1],
+                layer[offset + 2],
+                layer[offset + 3],
+            ])
+        };
 
-                token_client.transfer(&owner, &env.current_contract_address(), &amount);
+        layers.push_back((field(0), field(4), field(8), field(12)));
+    }
 
-                let Glyph {
-                    author,
-                    colors,
-                    legend,
-                    ..
-                } = env
-                    .storage()
-                    .persistent()
-                    .get::<Storage, Glyph>(&Storage::Glyph(buy))
-                    .ok_or(Error::GlyphNotMinted)?;
+    layers
+}
 
-                // transfer to glyph author
-                let glyph_author_royalty_rate = env
-                    .storage()
-                    .instance()
-                    .get::<Storage, i128>(&Storage::GlyphAuthorRoyaltyRate)
-                    .ok_or(Error::NotInitialized)?;
-                let author_amount = glyph_author_royalty_rate
-                    .fixed_mul_floor(&env, &amount, &100)
-                    .max(1);
+// draw the layers bottom up onto a transparent canvas, transparent layer pixels let the
+// layers below show through, then index the canvas by its colors in ascending order
+fn render_composite(
+    env: &Env,
+    layers: &Vec<(u32, u32, u32, u32)>,
+    width: u32,
+    height: u32,
+) -> Result<(Bytes, Vec<u32>), Error> {
+    let length = (width * height) as usize;
+    let mut canvas = [TRANSPARENT_COLOR; (MAX_GLYPH_WIDTH * MAX_GLYPH_WIDTH) as usize];
+
+    for (glyph_index, x, y, _) in layers.iter() {
+        let Glyph {
+            colors,
+            legend,
+            width: layer_width,
+            encoding,
+            ..
+        } = get_glyph(env, glyph_index).ok_or(Error::GlyphNotMinted)?;
+
+        // tiles are too big to draw and an animation would only show its first frame
+        if matches!(encoding, GlyphEncoding::Tiled(_))
+            || env
+                .storage()
+                .persistent()
+                .has::<Storage>(&Storage::GlyphAnimation(glyph_index))
+            || x >= width
+            || y >= height
+        {
+            return Err(Error::CompositeLayersInvalid);
+        }
 
-                update_royalties(&env, &author, &sac, &author_amount);
+        for (index, color) in decode_colors(env, &colors, &encoding).iter().enumerate() {
+            let canvas_x = x + index as u32 % layer_width;
+            let canvas_y = y + index as u32 / layer_width;
+            let color = legend.get_unchecked(color as u32);
 
-                // transfer to color owners
-                let colors_length = colors.len() as i128;
-                let color_owner_royalty_rate = env
-                    .storage()
-                    .instance()
-                    .get::<Storage, i128>(&Storage::ColorOwnerRoyaltyRate)
-                    .ok_or(Error::NotInitialized)?;
+            // layers hanging off the canvas are clipped
+            if canvas_x < width && canvas_y < height && color != TRANSPARENT_COLOR {
+                canvas[(canvas_y * width + canvas_x) as usize] = color;
+            }
+        }
+    }
 
-                let legend_length = legend.len() as usize;
-                let mut color_owner_amounts = 0;
+    let mut palette = [0u32; (MAX_GLYPH_WIDTH * MAX_GLYPH_WIDTH) as usize];
+    let mut palette_length = 0;
 
-                // TODO likely need to limit this to the first N ordered by highest count (125 storage gets)
-                for (index, count) in get_palette(colors).into_iter().enumerate() {
-                    if index >= legend_length {
-                        break;
-                    }
+    palette[..length].copy_from_slice(&canvas[..length]);
+    palette[..length].sort_unstable();
+
+    for index in 0..length {
+        if index == 0 || palette[index] != palette[palette_length - 1] {
+            palette[palette_length] = palette[index];
+            palette_length += 1;
+        }
+    }
 
-                    let color = legend.get_unchecked(index as u32);
+    if palette_length > 256 {
+        return Err(Error::LegendTooLong);
+    }
 
-                    match env
-                        .storage()
-                        .persistent()
-                        .get::<Storage, Address>(&Storage::ColorOwner(color))
-                    {
-                        Some(color_owner) => {
-                            let color_owner_amount = color_owner_royalty_rate
-                                .fixed_mul_floor(&env, &amount, &100)
-                                .fixed_mul_floor(&env, &(count as i128), &colors_length)
-                                .max(1);
+    let mut colors = Bytes::new(env);
+    let mut legend = Vec::new(env);
 
-                            update_royalties(&env, &color_owner, &sac, &color_owner_amount);
+    for color in canvas[..length].iter() {
+        colors.push_back(palette[..palette_length].binary_search(color).unwrap_or(0) as u8);
+    }
 
-                            color_owner_amounts += color_owner_amount;
-                        }
-                        None => continue,
-                    }
+    for color in palette[..palette_length].iter() {
+        legend.push_back(*color);
+    }
+
+    Ok((colors, legend))
+}
+
+// Sales only bump the glyph's cumulative color royalties, color owners collect their share
+// lazily. Only the colors in the glyph's capped palette accrue, keeping trade cost flat.
+fn settle_sale(
+    env: &Env,
+    glyph_index: u32,
+    seller: &Address,
+    sac: &Address,
+    amount: &i128,
+) -> Result<(), Error> {
+    let SaleShares {
+        author_amounts,
+        color_shares,
+        seller_amount,
+    } = get_sale_shares(env, glyph_index, amount)?;
+
+    // transfer to glyph author
+    for (recipient, recipient_amount) in author_amounts.iter() {
+        update_royalties(env, &recipient, sac, &recipient_amount);
+    }
+
+    // accrue to color owners
+    accrue_color_shares(env, glyph_index, sac, &color_shares);
+
+    // transfer asset to sell glyph owner
+    update_royalties(env, seller, sac, &seller_amount);
+
+    Ok(())
+}
+
+struct SaleShares {
+    author_amounts: Vec<(Address, i128)>,
+    color_shares: ColorShares,
+    seller_amount: i128,
+}
+
+// every share of a sale, computed without paying anyone so quotes and settlement can't diverge
+fn get_sale_shares(env: &Env, glyph_index: u32, amount: &i128) -> Result<SaleShares, Error> {
+    let Glyph {
+        author,
+        author_royalty_rate,
+        color_owner_royalty_rate,
+        ..
+    } = get_glyph(env, glyph_index).ok_or(Error::GlyphNotMinted)?;
+
+    let artwork_index = get_artwork_index(env, glyph_index)?;
+    let author_amount = author_royalty_rate.fixed_mul_floor(env, amount, &100);
+    let mut author_amounts = Vec::new(env);
+    let max_depth = get_glyph_lineage_max_depth(env);
+    let recipients = get_author_recipients(env, artwork_index, author);
+    let mut ancestor_budget = AncestorBudget {
+        ancestors: MAX_SALE_PAID_ANCESTORS,
+        // splits stored before the cap was lowered can leave nothing for the ancestors
+        recipients: MAX_SALE_AUTHOR_RECIPIENTS.saturating_sub(recipients.len()),
+    };
+
+    push_author_amounts(
+        env,
+        artwork_index,
+        recipients,
+        author_amount,
+        max_depth,
+        &mut ancestor_budget,
+        &mut author_amounts,
+    );
+
+    let glyph_palette = get_stored_glyph_palette(env, glyph_index)?;
+    let color_shares = get_color_shares(
+        env,
+        &glyph_palette,
+        &color_owner_royalty_rate.fixed_mul_floor(env, amount, &100),
+    )?;
+    let seller_amount = amount - author_amount - color_shares.total;
+
+    Ok(SaleShares {
+        author_amounts,
+        color_shares,
+        // the seller takes unclaimed colors and rounding so every share sums to amount
+        seller_amount,
+    })
+}
+
+// what's left for paying ancestors on one sale
+struct AncestorBudget {
+    ancestors: u32,
+    recipients: u32,
+}
+
+// the glyph author's share, less the lineage slice which cascades up to the parent authors.
+// Parents are paid before their own ancestors and once the budget runs out an ancestor's slice
+// stays with the child's recipients, recipients paid more than once are merged.
+fn push_author_amounts(
+    env: &Env,
+    glyph_index: u32,
+    recipients: Vec<(Address, u32)>,
+    author_amount: i128,
+    depth: u32,
+    ancestor_budget: &mut AncestorBudget,
+    author_amounts: &mut Vec<(Address, i128)>,
+) {
+    let mut author_amount_remaining = author_amount;
+
+    if depth > 0 && ancestor_budget.ancestors > 0 {
+        if let Some(GlyphLineage {
+            parents,
+            royalty_rate,
+        }) = env
+            .storage()
+            .persistent()
+            .get::<Storage, GlyphLineage>(&Storage::GlyphLineage(glyph_index))
+        {
+            let lineage_amount = royalty_rate.fixed_mul_floor(env, &author_amount, &100);
+            let parent_amount = lineage_amount / parents.len() as i128;
+            let mut paid_parents = Vec::new(env);
+
+            for (index, (parent, parent_author)) in parents.iter().enumerate() {
+                // the first parent picks up the rounding so the parents sum to the lineage amount
+                let parent_amount = if index == 0 {
+                    lineage_amount - parent_amount * (parents.len() as i128 - 1)
+                } else {
+                    parent_amount
+                };
+
+                if parent_amount == 0 || ancestor_budget.ancestors == 0 {
+                    continue;
                 }
 
-                // transfer asset to buy glyph owner
-                update_royalties(
-                    &env,
-                    &buy_glyph_owner,
-                    &sac,
-                    &(amount - author_amount - color_owner_amounts),
-                );
+                let parent_recipients = get_author_recipients(env, parent, parent_author);
 
-                // swap glyph ownership
-                env.storage()
-                    .persistent()
-                    .set::<Storage, Address>(&buy_glyph_owner_key, &owner);
+                if parent_recipients.len() <= ancestor_budget.recipients {
+                    ancestor_budget.ancestors -= 1;
+                    ancestor_budget.recipients -= parent_recipients.len();
 
-                // remove all open buy glyph sell offers
-                if env
-                    .storage()
-                    .persistent()
-                    .has::<Storage>(&open_glyph_buy_now_offers_key)
-                {
-                    env.storage()
-                        .persistent()
-                        .remove::<Storage>(&open_glyph_buy_now_offers_key);
+                    paid_parents.push_back((parent, parent_recipients, parent_amount));
+
+                    author_amount_remaining -= parent_amount;
                 }
+            }
 
-                env.events().publish(
-                    (Symbol::new(&env, "offer_sell_asset"), sell, buy),
-                    Some(owner.clone()),
+            for (parent, parent_recipients, parent_amount) in paid_parents.iter() {
+                push_author_amounts(
+                    env,
+                    parent,
+                    parent_recipients,
+                    parent_amount,
+                    depth - 1,
+                    ancestor_budget,
+                    author_amounts,
                 );
-
-                Ok(Some(owner))
             }
-            // No matching open counter offer. Add to buy glyph offers
-            Err(_index) => {
-                let offer_sell_asset_key = Storage::OfferSellAsset(buy, sac.clone(), amount);
+        }
+    }
 
-                let mut offers = env
-                    .storage()
-                    .persistent()
-                    .get::<Storage, Vec<Address>>(&offer_sell_asset_key)
-                    .unwrap_or(Vec::new(&env));
+    let split_amount = author_amount_remaining;
+    let mut first_recipient = None;
 
-                match offers.binary_search(&owner) {
-                    Ok(_index) => return Err(Error::OfferDuplicate),
-                    Err(index) => offers.insert(index, owner.clone()),
-                }
+    for (recipient, basis_points) in recipients.iter() {
+        let recipient_amount = split_amount.fixed_mul_floor(env, &(basis_points as i128), &10_000);
+        let index = add_author_amount(author_amounts, recipient, recipient_amount);
 
-                env.storage()
-                    .persistent()
-                    .set::<Storage, Vec<Address>>(&offer_sell_asset_key, &offers);
+        first_recipient.get_or_insert(index);
 
-                // transfer the asset to the contract for auto matching later
-                let token_client = token::TokenClient::new(&env, &sac);
+        author_amount_remaining -= recipient_amount;
+    }
 
-                token_client.transfer(&owner, &env.current_contract_address(), &amount);
+    // the first recipient picks up the rounding so the split sums to the author amount
+    if let Some(first_recipient) = first_recipient {
+        let (recipient, recipient_amount) = author_amounts.get_unchecked(first_recipient);
 
-                env.events().publish(
-                    (Symbol::new(&env, "offer_sell_asset"), sell, buy),
-                    None::<Address>,
-                );
+        author_amounts.set(
+            first_recipient,
+            (recipient, recipient_amount + author_amount_remaining),
+        );
+    }
+}
+
+// the glyph's split, or the author alone
+fn get_author_recipients(env: &Env, glyph_index: u32, author: Address) -> Vec<(Address, u32)> {
+    match env
+        .storage()
+        .persistent()
+        .get::<Storage, RoyaltySplit>(&Storage::GlyphAuthorSplit(glyph_index))
+    {
+        Some(RoyaltySplit { recipients, .. }) => recipients,
+        None => vec![env, (author, 10_000)],
+    }
+}
+
+// credit a recipient already being paid rather than paying them twice, returns their index
+fn add_author_amount(
+    author_amounts: &mut Vec<(Address, i128)>,
+    recipient: Address,
+    amount: i128,
+) -> u32 {
+    for (index, (author_recipient, author_amount)) in author_amounts.iter().enumerate() {
+        if author_recipient == recipient {
+            author_amounts.set(index as u32, (author_recipient, author_amount + amount));
+
+            return index as u32;
+        }
+    }
+
+    author_amounts.push_back((recipient, amount));
+
+    author_amounts.len() - 1
+}
+
+fn get_glyph_lineage_max_depth(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get::<Storage, u32>(&Storage::GlyphLineageMaxDepth)
+        .unwrap_or(MAX_GLYPH_LINEAGE_DEPTH)
+        .min(MAX_GLYPH_LINEAGE_DEPTH)
+}
+
+struct ColorShares {
+    color_amounts: Vec<(u32, u32, Option<Address>, i128)>,
+    remainder_fee: Option<(Address, i128)>,
+    total: i128, // what the color owners and remainder address are paid
+}
+
+// each royalty color's pixel share of amount and its current owner, unclaimed colors get
+// nothing and the colors beyond the royalty cap go to the remainder address
+fn get_color_shares(
+    env: &Env,
+    glyph_palette: &GlyphPalette,
+    amount: &i128,
+) -> Result<ColorShares, Error> {
+    let GlyphPalette { counts, pixels } = glyph_palette;
+    let pixels = *pixels as i128;
+    let mut color_amounts = Vec::new(env);
+    let mut remainder_count = pixels;
+    let mut remainder_fee = None;
+    let mut total = 0;
+
+    for (color, count) in counts.iter() {
+        let color_owner = env
+            .storage()
+            .persistent()
+            .get::<Storage, Address>(&Storage::ColorOwner(color));
+        let color_amount = match color_owner {
+            Some(_) => amount.fixed_mul_floor(env, &(count as i128), &pixels),
+            None => 0,
+        };
+
+        color_amounts.push_back((color, count, color_owner, color_amount));
+
+        remainder_count -= count as i128;
+        total += color_amount;
+    }
+
+    if remainder_count > 0 {
+        let remainder_address = match env
+            .storage()
+            .instance()
+            .get::<Storage, Address>(&Storage::ColorRoyaltyRemainderAddress)
+        {
+            Some(remainder_address) => remainder_address,
+            None => env
+                .storage()
+                .instance()
+                .get::<Storage, Address>(&Storage::FeeAddress)
+                .ok_or(Error::NotInitialized)?,
+        };
+        let remainder_amount = amount.fixed_mul_floor(env, &remainder_count, &pixels);
+
+        remainder_fee = Some((remainder_address, remainder_amount));
+        total += remainder_amount;
+    }
+
+    Ok(ColorShares {
+        color_amounts,
+        remainder_fee,
+        total,
+    })
+}
+
+// One write for the glyph's cumulative royalties whatever its legend, a color's collected
+// marker is only written when it first accrues or its owner has changed since the last time
+fn accrue_color_shares(env: &Env, glyph_index: u32, sac: &Address, color_shares: &ColorShares) {
+    let ColorShares {
+        color_amounts,
+        remainder_fee,
+        ..
+    } = color_shares;
+
+    let glyph_royalties_key = Storage::GlyphRoyalties(glyph_index, sac.clone());
+    let mut glyph_royalties = env
+        .storage()
+        .persistent()
+        .get::<Storage, Vec<(u32, i128)>>(&glyph_royalties_key)
+        .unwrap_or(Vec::new(env));
+
+    for (index, (color, _count, color_owner, color_amount)) in color_amounts.iter().enumerate() {
+        let index = index as u32;
+        let cumulative = match glyph_royalties.get(index) {
+            Some((_, cumulative)) => cumulative,
+            None => {
+                glyph_royalties.push_back((color, 0));
+                0
+            }
+        };
+
+        let Some(color_owner) = color_owner else {
+            continue;
+        };
 
-                Ok(None)
-            }
+        if color_amount == 0 {
+            continue;
         }
-    }
-    pub fn offer_sell_glyph_remove(
-        env: Env,
-        sell: u32,
-        buy: Option<OfferBuy>,
-    ) -> Result<(), Error> {
-        let glyph_owner_key = Storage::GlyphOwner(sell);
-        let offer_sell_glyph_key = Storage::OfferSellGlyph(sell);
 
-        let glyph_owner = env
+        let collected_key = Storage::ColorRoyaltiesCollected(glyph_index, color, sac.clone());
+
+        match env
             .storage()
             .persistent()
-            .get::<Storage, Address>(&glyph_owner_key)
-            .ok_or(Error::GlyphNotMinted)?;
+            .get::<Storage, ColorRoyalties>(&collected_key)
+        {
+            Some(ColorRoyalties { owner, .. }) if owner == color_owner => {}
+            previous => {
+                // a previous owner keeps what accrued while they held the color
+                if let Some(ColorRoyalties { owner, collected }) = previous {
+                    update_royalties(env, &owner, sac, &(cumulative - collected));
+                }
 
-        glyph_owner.require_auth();
+                env.storage().persistent().set::<Storage, ColorRoyalties>(
+                    &collected_key,
+                    &ColorRoyalties {
+                        owner: color_owner,
+                        collected: cumulative,
+                    },
+                );
+            }
+        }
 
-        env.events().publish(
-            (
-                Symbol::new(&env, "offer_sell_glyph_remove"),
-                sell,
-                buy.clone(),
-            ),
-            (),
-        );
+        glyph_royalties.set(index, (color, cumulative + color_amount));
+    }
 
-        match buy {
-            Some(buy) => {
-                let mut offers = env
-                    .storage()
-                    .persistent()
-                    .get::<Storage, Vec<OfferBuy>>(&offer_sell_glyph_key)
-                    .unwrap_or(Vec::new(&env));
+    env.storage()
+        .persistent()
+        .set::<Storage, Vec<(u32, i128)>>(&glyph_royalties_key, &glyph_royalties);
 
-                match offers.binary_search(&buy) {
-                    Ok(index) => {
-                        offers.remove(index);
+    if let Some((remainder_address, remainder_amount)) = remainder_fee {
+        update_royalties(env, remainder_address, sac, remainder_amount);
+    }
+}
 
-                        env.storage()
-                            .persistent()
-                            .set::<Storage, Vec<OfferBuy>>(&offer_sell_glyph_key, &offers);
+// what the glyph's color has accrued since its owner last collected, and the glyph's cumulative
+// royalties for it. Returns (royalties owed, cumulative royalties).
+fn get_color_royalties(env: &Env, color: u32, glyph_index: u32, sac: &Address) -> (i128, i128) {
+    let cumulative = env
+        .storage()
+        .persistent()
+        .get::<Storage, Vec<(u32, i128)>>(&Storage::GlyphRoyalties(glyph_index, sac.clone()))
+        .and_then(|glyph_royalties| {
+            glyph_royalties
+                .iter()
+                .find(|(glyph_color, _)| *glyph_color == color)
+        })
+        .map(|(_, cumulative)| cumulative)
+        .unwrap_or(0);
 
-                        Ok(())
-                    }
-                    Err(_index) => Err(Error::OfferNotFound),
-                }
-            }
-            None => {
-                if env
-                    .storage()
-                    .persistent()
-                    .has::<Storage>(&offer_sell_glyph_key)
-                {
-                    env.storage()
-                        .persistent()
-                        .remove::<Storage>(&offer_sell_glyph_key);
-                }
+    match env.storage().persistent().get::<Storage, ColorRoyalties>(
+        &Storage::ColorRoyaltiesCollected(glyph_index, color, sac.clone()),
+    ) {
+        Some(ColorRoyalties { collected, .. }) => (cumulative - collected, cumulative),
+        None => (0, cumulative),
+    }
+}
 
-                Ok(())
-            }
-        }
+// glyphs minted before palettes were stored get theirs on first use
+fn get_stored_glyph_palette(env: &Env, glyph_index: u32) -> Result<GlyphPalette, Error> {
+    let glyph_palette_key = Storage::GlyphPalette(glyph_index);
+
+    if let Some(glyph_palette) = env
+        .storage()
+        .persistent()
+        .get::<Storage, GlyphPalette>(&glyph_palette_key)
+    {
+        return Ok(glyph_palette);
     }
-    pub fn offer_sell_asset_remove(env: Env, sell: OfferSellAsset, buy: u32) -> Result<(), Error> {
-        let OfferSellAsset(owner, sac, amount) = sell.clone();
 
-        owner.require_auth();
+    let Glyph {
+        colors,
+        legend,
+        encoding,
+        ..
+    } = get_glyph(env, glyph_index).ok_or(Error::GlyphNotMinted)?;
+    let glyph_palette = get_glyph_palette(env, get_palette(colors, &encoding), &legend)?;
 
-        let offer_sell_asset_key = Storage::OfferSellAsset(buy, sac.clone(), amount);
+    env.storage()
+        .persistent()
+        .set::<Storage, GlyphPalette>(&glyph_palette_key, &glyph_palette);
 
-        let mut offers = env
-            .storage()
-            .persistent()
-            .get::<Storage, Vec<Address>>(&offer_sell_asset_key)
-            .unwrap_or(Vec::new(&env));
+    Ok(glyph_palette)
+}
 
-        match offers.binary_search(&owner) {
-            Ok(index) => {
-                offers.remove(index);
-            }
-            Err(_index) => return Err(Error::OfferNotFound),
+// legend colors ordered by pixel count (ties by color) and capped at ColorRoyaltyMaxColors,
+// transparent pixels are left out of both the colors and the pixel total
+fn get_glyph_palette(
+    env: &Env,
+    counts: [u32; 256],
+    legend: &Vec<u32>,
+) -> Result<GlyphPalette, Error> {
+    let max_colors = env
+        .storage()
+        .instance()
+        .get::<Storage, u32>(&Storage::ColorRoyaltyMaxColors)
+        .unwrap_or(DEFAULT_COLOR_ROYALTY_MAX_COLORS)
+        .min(MAX_COLOR_ROYALTY_MAX_COLORS);
+
+    let mut palette = [(0u32, 0u32); 256];
+    let mut palette_length = 0;
+    let mut pixels = 0;
+
+    for (index, count) in counts.into_iter().enumerate() {
+        if index >= legend.len() as usize {
+            break;
         }
 
-        env.storage()
-            .persistent()
-            .set::<Storage, Vec<Address>>(&offer_sell_asset_key, &offers);
+        let color = legend.get_unchecked(index as u32);
 
-        // refund the asset back to the user from the contract
-        let token_client = token::TokenClient::new(&env, &sac);
+        if count > 0 && color != TRANSPARENT_COLOR {
+            palette[palette_length] = (color, count);
+            palette_length += 1;
+            pixels += count;
+        }
+    }
 
-        token_client.transfer(&env.current_contract_address(), &owner, &amount);
+    if pixels == 0 {
+        return Err(Error::GlyphEmpty);
+    }
 
-        env.events().publish(
-            (Symbol::new(&env, "offer_sell_asset_remove"), sell, buy),
-            (),
-        );
+    palette[..palette_length].sort_unstable_by(|(a_color, a_count), (b_color, b_count)| {
+        b_count.cmp(a_count).then(a_color.cmp(b_color))
+    });
 
-        Ok(())
+    let mut sorted_palette = Vec::new(env);
+
+    for (color, count) in palette[..palette_length].iter().take(max_colors as usize) {
+        sorted_palette.push_back((*color, *count));
     }
-    pub fn offer_sell_glyph_get(
-        env: Env,
-        sell: u32,
-        buy: Option<OfferBuy>,
-    ) -> Result<Option<u32>, Error> {
-        let offers = env
+
+    Ok(GlyphPalette {
+        counts: sorted_palette,
+        pixels,
+    })
+}
+
+// the glyph a recolor reads its pixels through with its colors decoded
+fn get_recolor_parent(env: &Env, parent: u32) -> Result<(u32, Glyph), Error> {
+    let glyph = get_glyph(env, parent).ok_or(Error::GlyphNotMinted)?;
+
+    // always point at the glyph which holds the pixels, tiles and frames can't be recolored
+    let (parent, glyph) = match glyph.encoding {
+        GlyphEncoding::Recolor(parent) => {
+            (parent, get_glyph(env, parent).ok_or(Error::GlyphNotMinted)?)
+        }
+        GlyphEncoding::Tiled(_) => return Err(Error::RecolorParentInvalid),
+        _ if env
             .storage()
             .persistent()
-            .get::<Storage, Vec<OfferBuy>>(&Storage::OfferSellGlyph(sell))
-            .unwrap_or(Vec::new(&env));
-
-        match buy {
-            Some(buy) => match offers.binary_search(&buy) {
-                Ok(index) => Ok(Some(index)),
-                Err(_index) => Ok(None),
-            },
-            None => {
-                if offers.is_empty() {
-                    Ok(None)
-                } else {
-                    Ok(Some(offers.len()))
-                }
-            }
+            .has::<Storage>(&Storage::GlyphAnimation(parent)) =>
+        {
+            return Err(Error::RecolorParentInvalid)
         }
+        _ => (parent, glyph),
+    };
+
+    Ok((
+        parent,
+        Glyph {
+            colors: decode_colors(env, &glyph.colors, &glyph.encoding),
+            encoding: GlyphEncoding::Raw,
+            ..glyph
+        },
+    ))
+}
+
+fn check_recolor_legend(legend: &Vec<u32>, parent_legend: &Vec<u32>) -> Result<(), Error> {
+    // the legend maps the same indexes to new colors, in any order but without repeats
+    if legend.len() != parent_legend.len() {
+        return Err(Error::RecolorParentInvalid);
     }
-    pub fn offer_sell_asset_get(
-        env: Env,
-        sell: OfferSellAssetGet,
-        buy: u32,
-    ) -> Result<Option<u32>, Error> {
-        let OfferSellAssetGet(owner, sell, amount) = sell;
 
-        let offers = env
-            .storage()
-            .persistent()
-            .get::<Storage, Vec<Address>>(&Storage::OfferSellAsset(buy, sell, amount))
-            .unwrap_or(Vec::new(&env));
+    for (index, color) in legend.iter().enumerate() {
+        if color > MAX_COLOR && color != TRANSPARENT_COLOR {
+            return Err(Error::ColorOutOfRange);
+        }
 
-        match owner {
-            Some(owner) => match offers.binary_search(&owner) {
-                Ok(index) => Ok(Some(index)),
-                Err(_index) => Ok(None),
-            },
-            None => {
-                if offers.is_empty() {
-                    Ok(None)
-                } else {
-                    Ok(Some(offers.len()))
-                }
-            }
+        if legend.first_index_of(color) != Some(index as u32) {
+            return Err(Error::LegendDuplicate);
         }
     }
 
-    pub fn royalties_get(env: Env, owner: Address, sac: Address) -> Result<i128, Error> {
-        let royalties = env
-            .storage()
-            .persistent()
-            .get::<Storage, i128>(&Storage::Royalties(owner, sac))
-            .unwrap_or(0);
+    Ok(())
+}
 
-        Ok(royalties)
+fn get_composite_draw_layers(
+    env: &Env,
+    layers: &Vec<(u32, u32, u32, u32)>,
+    width: u32,
+    height: u32,
+) -> Result<Vec<(u32, u32, u32, u32)>, Error> {
+    if layers.is_empty() || layers.len() > MAX_GLYPH_LAYERS {
+        return Err(Error::CompositeLayersInvalid);
     }
-    pub fn royalties_claim(env: Env, owner: Address, sac: Address) -> Result<i128, Error> {
-        let royalties_key = Storage::Royalties(owner.clone(), sac.clone());
 
-        let royalties = env
-            .storage()
-            .persistent()
-            .get::<Storage, i128>(&royalties_key)
-            .unwrap_or(0);
+    if width == 0 || width > MAX_GLYPH_WIDTH {
+        return Err(Error::GlyphWidthInvalid);
+    }
 
-        if royalties == 0 {
-            return Err(Error::NoRoyaltiesToClaim);
-        }
+    if height == 0 || height > MAX_GLYPH_WIDTH {
+        return Err(Error::GlyphTooBig);
+    }
 
-        let token_client = token::TokenClient::new(&env, &sac);
+    // draw order is by z, layers sharing a z draw in the order given
+    // (z, position, glyph index, x, y)
+    let mut sorted_layers = [(0u32, 0u32, 0u32, 0u32, 0u32); MAX_GLYPH_LAYERS as usize];
 
-        token_client.transfer(&env.current_contract_address(), &owner, &royalties);
+    for (position, (glyph_index, x, y, z)) in layers.iter().enumerate() {
+        sorted_layers[position] = (z, position as u32, glyph_index, x, y);
+    }
 
-        env.storage()
-            .persistent()
-            .set::<Storage, i128>(&royalties_key, &0);
+    sorted_layers[..layers.len() as usize].sort_unstable();
 
-        Ok(royalties)
+    let mut draw_layers = Vec::new(env);
+
+    for (z, _, glyph_index, x, y) in sorted_layers[..layers.len() as usize].iter() {
+        draw_layers.push_back((*glyph_index, *x, *y, *z));
     }
+
+    Ok(draw_layers)
 }
 
-#[contractimpl]
-impl CustomAccountInterface for Contract {
-    type Error = Error;
-    type Signature = Option<Vec<Val>>;
+fn get_animation_palette(
+    frames: &Vec<Bytes>,
+    legend: &Vec<u32>,
+    width: u32,
+) -> Result<[u32; 256], Error> {
+    if frames.len() < 2 || frames.len() > MAX_GLYPH_FRAMES {
+        return Err(Error::GlyphFramesInvalid);
+    }
 
-    #[allow(non_snake_case)]
-    fn __check_auth(
-        env: Env,
-        _signature_payload: Hash<32>,
-        _signatures: Option<Vec<Val>>,
-        _auth_contexts: Vec<Context>,
-    ) -> Result<(), Error> {
-        let admin = env
-            .storage()
-            .instance()
-            .get::<Storage, Address>(&Storage::Admin)
-            .ok_or(Error::NotInitialized)?;
+    let first_frame = frames.get_unchecked(0);
 
-        admin.require_auth_for_args(vec![&env]);
+    check_glyph_size(&first_frame, width)?;
+    check_legend(legend)?;
 
-        Ok(())
-    }
-}
+    // every frame shares the legend so colors are weighted by their pixels across all frames
+    let mut palette = [0u32; 256];
 
-fn pay_fee(env: &Env, fee_key: Storage, source: &Address) -> Result<(), Error> {
-    let fee_address = env
-        .storage()
-        .instance()
-        .get::<Storage, Address>(&Storage::FeeAddress)
-        .ok_or(Error::NotInitialized)?;
-    let fee_amount = env
-        .storage()
-        .instance()
-        .get::<Storage, i128>(&fee_key)
-        .ok_or(Error::NotInitialized)?;
-    let fee_sac = env
-        .storage()
-        .instance()
-        .get::<Storage, Address>(&Storage::FeeSAC)
-        .ok_or(Error::NotInitialized)?;
-    let fee_client = token::TokenClient::new(&env, &fee_sac);
+    for frame in frames.iter() {
+        if frame.len() != first_frame.len() {
+            return Err(Error::GlyphFramesInvalid);
+        }
 
-    source.require_auth();
+        for (index, count) in get_palette(frame, &GlyphEncoding::Raw)
+            .into_iter()
+            .enumerate()
+        {
+            palette[index] += count;
+        }
+    }
 
-    fee_client.transfer(&source, &fee_address, &fee_amount);
+    check_glyph_palette(&palette, legend)?;
 
-    Ok(())
+    Ok(palette)
 }
 
-fn get_palette(colors: Bytes) -> [u32; 256] {
-    let colors_length = colors.len() as usize;
+fn get_session_palette(session: &GlyphMintSession) -> Result<[u32; 256], Error> {
+    let tiles = (session.width * session.height).div_ceil(GLYPH_TILE_LENGTH);
 
-    // let mut colors_bytes = [0u8; 8192];
-    let mut palette_bytes = [0u32; 256];
+    if session.tiles_uploaded < tiles {
+        return Err(Error::GlyphTilesMissing);
+    }
 
-    // colors.copy_into_slice(&mut colors_bytes);
-    // TODO should we loop over colors or colors_bytes?
+    let mut palette = [0u32; 256];
 
-    for (index, color) in colors.into_iter().enumerate() {
-        if index > colors_length {
-            break;
+    for (index, count) in session.counts.iter().enumerate() {
+        if count == 0 {
+            return Err(Error::LegendColorUnused);
         }
 
-        palette_bytes[color as usize] += 1;
+        palette[index] = count;
     }
 
-    palette_bytes
+    Ok(palette)
 }
 
 fn update_royalties(env: &Env, owner: &Address, sac: &Address, amount: &i128) {
+    if *amount == 0 {
+        return;
+    }
+
     let royalties_key = Storage::Royalties(owner.clone(), sac.clone());
 
     let royalties = env
@@ -993,4 +5210,74 @@ fn update_royalties(env: &Env, owner: &Address, sac: &Address, amount: &i128) {
     env.storage()
         .persistent()
         .set::<Storage, i128>(&royalties_key, &(royalties + amount));
+
+    // index the asset so wallets can discover which royalties are claimable
+    if royalties == 0 {
+        let royalties_assets_key = Storage::RoyaltiesAssets(owner.clone());
+        let mut royalties_assets = env
+            .storage()
+            .persistent()
+            .get::<Storage, Vec<Address>>(&royalties_assets_key)
+            .unwrap_or(Vec::new(env));
+
+        if let Err(index) = royalties_assets.binary_search(sac) {
+            royalties_assets.insert(index, sac.clone());
+
+            env.storage()
+                .persistent()
+                .set::<Storage, Vec<Address>>(&royalties_assets_key, &royalties_assets);
+        }
+    }
+}
+
+fn claim_royalties(env: &Env, owner: &Address, sac: &Address, to: &Address) -> i128 {
+    let royalties_key = Storage::Royalties(owner.clone(), sac.clone());
+
+    let royalties = env
+        .storage()
+        .persistent()
+        .get::<Storage, i128>(&royalties_key)
+        .unwrap_or(0);
+
+    if royalties == 0 {
+        return 0;
+    }
+
+    let token_client = token::TokenClient::new(env, sac);
+
+    token_client.transfer(&env.current_contract_address(), to, &royalties);
+
+    env.storage().persistent().remove::<Storage>(&royalties_key);
+
+    let royalties_assets_key = Storage::RoyaltiesAssets(owner.clone());
+    let mut royalties_assets = env
+        .storage()
+        .persistent()
+        .get::<Storage, Vec<Address>>(&royalties_assets_key)
+        .unwrap_or(Vec::new(env));
+
+    if let Ok(index) = royalties_assets.binary_search(sac) {
+        royalties_assets.remove(index);
+
+        if royalties_assets.is_empty() {
+            env.storage()
+                .persistent()
+                .remove::<Storage>(&royalties_assets_key);
+        } else {
+            env.storage()
+                .persistent()
+                .set::<Storage, Vec<Address>>(&royalties_assets_key, &royalties_assets);
+        }
+    }
+
+    env.events().publish(
+        (
+            Symbol::new(env, "royalties_claim"),
+            owner.clone(),
+            sac.clone(),
+        ),
+        (to.clone(), royalties),
+    );
+
+    royalties
 }
@@ -0,0 +1,238 @@
+use soroban_sdk::{contract, contractimpl, testutils::Address as _, token, Address, Env};
+
+use crate::{
+    tests::utils::{initialize, mint, Init},
+    Error, OfferBuy, OfferSellAsset,
+};
+
+#[test]
+fn test_asset_allowlist() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        admin,
+        contract_id,
+        client,
+        fee_sac_address,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let amount = 100_0000000;
+
+    let user_1 = Address::generate(&env);
+    let user_2 = Address::generate(&env);
+
+    let other_sac = env.register_stellar_asset_contract_v2(admin.clone());
+    let other_sac_address = other_sac.address();
+    let other_sac_admin_client = token::StellarAssetClient::new(&env, &other_sac_address);
+
+    fee_sac_admin_client.mint(&user_1, &glyph_fee);
+    other_sac_admin_client.mint(&user_2, &amount);
+
+    let glyph_1_hash = mint(&env, &client, &contract_id, &user_1, &user_1, &user_1);
+
+    // ensure only the fee asset is accepted by default
+    let assets = client.assets_get();
+
+    assert_eq!(assets.len(), 1);
+    assert_eq!(assets.get(fee_sac_address.clone()), Some(None));
+
+    // ensure unknown assets can't be escrowed or listed for
+    let res = client.try_offer_sell_asset(
+        &OfferSellAsset(user_2.clone(), other_sac_address.clone(), amount),
+        &glyph_1_hash,
+    );
+
+    assert_eq!(res, Err(Ok(Error::AssetNotAllowed)));
+
+    let res = client.try_offer_sell_glyph(
+        &glyph_1_hash,
+        &OfferBuy::Asset(other_sac_address.clone(), amount),
+    );
+
+    assert_eq!(res, Err(Ok(Error::AssetNotAllowed)));
+
+    // allow the asset with a minimum amount
+    client.asset_allow(&other_sac_address, &Some(amount));
+
+    let assets = client.assets_get();
+
+    assert_eq!(assets.len(), 2);
+    assert_eq!(assets.get(other_sac_address.clone()), Some(Some(amount)));
+
+    let res = client.try_offer_sell_asset(
+        &OfferSellAsset(user_2.clone(), other_sac_address.clone(), amount - 1),
+        &glyph_1_hash,
+    );
+
+    assert_eq!(res, Err(Ok(Error::AssetAmountTooLow)));
+
+    client.offer_sell_asset(
+        &OfferSellAsset(user_2.clone(), other_sac_address.clone(), amount),
+        &glyph_1_hash,
+    );
+
+    // ensure disallowed assets can no longer be listed for
+    client.asset_disallow(&other_sac_address);
+
+    let res = client.try_offer_sell_glyph(
+        &glyph_1_hash,
+        &OfferBuy::Asset(other_sac_address.clone(), amount),
+    );
+
+    assert_eq!(res, Err(Ok(Error::AssetNotAllowed)));
+
+    let res = client.try_asset_disallow(&other_sac_address);
+
+    assert_eq!(res, Err(Ok(Error::AssetNotAllowed)));
+
+    // ensure existing escrow can still be refunded
+    client.offer_sell_asset_remove(
+        &OfferSellAsset(user_2.clone(), other_sac_address.clone(), amount),
+        &glyph_1_hash,
+    );
+
+    // ensure a new fee asset is allowed once it's set
+    let next_sac = env.register_stellar_asset_contract_v2(admin.clone());
+    let next_sac_address = next_sac.address();
+
+    client.update(
+        &None,
+        &Some(next_sac_address.clone()),
+        &None,
+        &None,
+        &None,
+        &None,
+        &None,
+    );
+
+    let assets = client.assets_get();
+
+    assert_eq!(assets.len(), 2);
+    assert_eq!(assets.get(next_sac_address.clone()), Some(None));
+
+    // ensure an already allowed fee asset keeps its min amount
+    client.asset_allow(&other_sac_address, &Some(amount));
+
+    client.update(
+        &None,
+        &Some(other_sac_address.clone()),
+        &None,
+        &None,
+        &None,
+        &None,
+        &None,
+    );
+
+    let assets = client.assets_get();
+
+    assert_eq!(assets.len(), 3);
+    assert_eq!(assets.get(other_sac_address.clone()), Some(Some(amount)));
+}
+
+// a SEP-41 style token which burns 1% of every transfer
+#[contract]
+pub struct FeeOnTransferToken;
+
+#[contractimpl]
+impl FeeOnTransferToken {
+    pub fn mint(env: Env, to: Address, amount: i128) {
+        let balance = Self::balance(env.clone(), to.clone());
+
+        env.storage().persistent().set(&to, &(balance + amount));
+    }
+    pub fn balance(env: Env, id: Address) -> i128 {
+        env.storage().persistent().get(&id).unwrap_or(0)
+    }
+    pub fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+        from.require_auth();
+
+        let from_balance = Self::balance(env.clone(), from.clone());
+        let to_balance = Self::balance(env.clone(), to.clone());
+
+        env.storage()
+            .persistent()
+            .set(&from, &(from_balance - amount));
+        env.storage()
+            .persistent()
+            .set(&to, &(to_balance + amount - amount / 100));
+    }
+}
+
+#[test]
+fn test_asset_received_short() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        contract_id,
+        client,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let amount = 100_0000000;
+
+    let user_1 = Address::generate(&env);
+    let user_2 = Address::generate(&env);
+
+    let token_address = env.register(FeeOnTransferToken, ());
+    let token_client = FeeOnTransferTokenClient::new(&env, &token_address);
+
+    fee_sac_admin_client.mint(&user_1, &glyph_fee);
+    token_client.mint(&user_2, &amount);
+
+    client.asset_allow(&token_address, &None);
+
+    let glyph_1_hash = mint(&env, &client, &contract_id, &user_1, &user_1, &user_1);
+
+    // ensure bids which don't fully arrive are rejected
+    let res = client.try_offer_sell_asset(
+        &OfferSellAsset(user_2.clone(), token_address.clone(), amount),
+        &glyph_1_hash,
+    );
+
+    assert_eq!(res, Err(Ok(Error::AssetReceivedShort)));
+
+    // ensure taking a listing which doesn't fully arrive is rejected
+    client.offer_sell_glyph(
+        &glyph_1_hash,
+        &OfferBuy::Asset(token_address.clone(), amount),
+    );
+
+    let res = client.try_offer_sell_asset(
+        &OfferSellAsset(user_2.clone(), token_address.clone(), amount),
+        &glyph_1_hash,
+    );
+
+    assert_eq!(res, Err(Ok(Error::AssetReceivedShort)));
+
+    assert_eq!(token_client.balance(&user_2), amount);
+    assert_eq!(token_client.balance(&contract_id), 0);
+}
@@ -1,9 +1,14 @@
-use soroban_sdk::{testutils::Address as _, Address, Env};
+use soroban_sdk::{testutils::Address as _, vec, xdr::ToXdr, Address, Bytes, Env, String, Vec};
 
 use std::println;
 extern crate std;
 
-use crate::tests::utils::{initialize, mint, Init};
+use crate::{
+    tests::utils::{initialize, mint, Init},
+    Error, Glyph, GlyphAnimation, GlyphEncoding, GlyphMetadata, GlyphMintOptions, GlyphPalette,
+    GlyphV1, OfferBuy, OfferSellAsset, OfferSellAssetGet, Storage, SYMMETRY_FLIP_HORIZONTAL,
+    SYMMETRY_FLIP_VERTICAL, SYMMETRY_ROTATE,
+};
 
 #[test]
 fn test_glyph_mint() {
@@ -62,3 +67,1214 @@ fn test_glyph_mint() {
 fn test_glyph_owner_transfer() {
     // TODO
 }
+
+#[test]
+fn test_glyph_metadata() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        contract_id,
+        client,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let user = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&user, &glyph_fee);
+
+    let glyph_index = mint(&env, &client, &contract_id, &user, &user, &user);
+
+    assert_eq!(
+        client.glyph_metadata_get(&glyph_index),
+        GlyphMetadata {
+            title: String::from_str(&env, "Hello World"),
+            story: String::from_str(&env, "Lorem Ipsum"),
+        }
+    );
+
+    let glyph = client.glyph_get(&glyph_index);
+
+    // ensure the author can edit the metadata without touching the glyph
+    client.glyph_metadata_set(
+        &glyph_index,
+        &String::from_str(&env, "Goodbye World"),
+        &String::from_str(&env, ""),
+    );
+
+    assert_eq!(
+        client.glyph_metadata_get(&glyph_index),
+        GlyphMetadata {
+            title: String::from_str(&env, "Goodbye World"),
+            story: String::from_str(&env, ""),
+        }
+    );
+    assert_eq!(client.glyph_get(&glyph_index), glyph);
+
+    // ensure the length caps are enforced
+    let res = client.try_glyph_metadata_set(
+        &glyph_index,
+        &String::from_str(&env, &"a".repeat(65)),
+        &String::from_str(&env, ""),
+    );
+
+    assert_eq!(res, Err(Ok(Error::GlyphTitleTooLong)));
+
+    let res = client.try_glyph_metadata_set(
+        &glyph_index,
+        &String::from_str(&env, ""),
+        &String::from_str(&env, &"a".repeat(1025)),
+    );
+
+    assert_eq!(res, Err(Ok(Error::GlyphStoryTooLong)));
+
+    let res = client.try_glyph_metadata_get(&(glyph_index + 1));
+
+    assert_eq!(res, Err(Ok(Error::GlyphNotMinted)));
+}
+
+#[test]
+fn test_glyph_mint_validation() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        client,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let user = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&user, &glyph_fee);
+
+    let try_mint = |colors: &[u8], legend: Vec<u32>, width: u32| {
+        client.try_glyph_mint(
+            &user,
+            &user,
+            &user,
+            &Bytes::from_slice(&env, colors),
+            &legend,
+            &width,
+            &String::from_str(&env, "Hello World"),
+            &String::from_str(&env, "Lorem Ipsum"),
+            &None,
+        )
+    };
+
+    let res = try_mint(&[], vec![&env], 1);
+
+    assert_eq!(res, Err(Ok(Error::GlyphEmpty)));
+
+    let res = try_mint(&[0, 1, 1, 0], vec![&env, 0, 1], 0);
+
+    assert_eq!(res, Err(Ok(Error::GlyphWidthInvalid)));
+
+    let res = try_mint(&[0; 46], vec![&env, 0], 46);
+
+    assert_eq!(res, Err(Ok(Error::GlyphWidthInvalid)));
+
+    let res = try_mint(&[0, 1, 1], vec![&env, 0, 1], 2);
+
+    assert_eq!(res, Err(Ok(Error::GlyphNotRectangular)));
+
+    let res = try_mint(&[0; 46], vec![&env, 0], 1);
+
+    assert_eq!(res, Err(Ok(Error::GlyphTooBig)));
+
+    let mut legend = Vec::new(&env);
+
+    for color in 0..257 {
+        legend.push_back(color);
+    }
+
+    let res = try_mint(&[0, 1, 1, 0], legend, 2);
+
+    assert_eq!(res, Err(Ok(Error::LegendTooLong)));
+
+    let res = try_mint(&[0, 1, 1, 0], vec![&env, 0, 16777217], 2);
+
+    assert_eq!(res, Err(Ok(Error::ColorOutOfRange)));
+
+    let res = try_mint(&[0, 1, 1, 0], vec![&env, 1, 1], 2);
+
+    assert_eq!(res, Err(Ok(Error::LegendDuplicate)));
+
+    let res = try_mint(&[0, 1, 1, 0], vec![&env, 1, 0], 2);
+
+    assert_eq!(res, Err(Ok(Error::LegendUnordered)));
+
+    let res = try_mint(&[0, 1, 2, 0], vec![&env, 0, 1], 2);
+
+    assert_eq!(res, Err(Ok(Error::ColorNotInLegend)));
+
+    let res = try_mint(&[0, 1, 1, 0], vec![&env, 0, 1, 2], 2);
+
+    assert_eq!(res, Err(Ok(Error::LegendColorUnused)));
+
+    let res = try_mint(&[0, 1, 1, 0], vec![&env, 0, 1], 2);
+
+    assert_eq!(res, Ok(Ok(1)));
+}
+
+#[test]
+fn test_glyph_hash_compute() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        client,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let user = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&user, &glyph_fee);
+
+    let colors = Bytes::from_array(&env, &[0, 1, 1, 0]);
+    let legend = vec![&env, 0, 16777215];
+
+    let (glyph_hash, glyph_index) = client.glyph_hash_compute(&colors, &legend, &2);
+
+    assert_eq!(glyph_index, None);
+
+    let glyph_index = client.glyph_mint(
+        &user,
+        &user,
+        &user,
+        &colors,
+        &legend,
+        &2,
+        &String::from_str(&env, "Hello World"),
+        &String::from_str(&env, "Lorem Ipsum"),
+        &None,
+    );
+
+    assert_eq!(
+        client.glyph_hash_compute(&colors, &legend, &2),
+        (glyph_hash.clone(), Some(glyph_index))
+    );
+
+    // ensure a permuted legend, unused and duplicate colors all hash to the same image
+    for (colors, legend) in [
+        ([1, 0, 0, 1], vec![&env, 16777215, 0]),
+        ([0, 2, 2, 0], vec![&env, 0, 255, 16777215]),
+        ([0, 1, 2, 0], vec![&env, 0, 16777215, 16777215]),
+    ] {
+        assert_eq!(
+            client.glyph_hash_compute(&Bytes::from_array(&env, &colors), &legend, &2),
+            (glyph_hash.clone(), Some(glyph_index))
+        );
+    }
+
+    // ensure the width is still part of the image
+    let (glyph_hash_2, glyph_index_2) = client.glyph_hash_compute(&colors, &legend, &4);
+
+    assert_ne!(glyph_hash_2, glyph_hash);
+    assert_eq!(glyph_index_2, None);
+}
+
+#[test]
+fn test_glyph_symmetric_duplicate() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        client,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let user = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&user, &(glyph_fee * 2));
+
+    client.glyph_symmetry_policy_set(
+        &(SYMMETRY_FLIP_HORIZONTAL | SYMMETRY_FLIP_VERTICAL | SYMMETRY_ROTATE),
+    );
+
+    let try_mint = |colors: &[u8], width: u32| {
+        client.try_glyph_mint(
+            &user,
+            &user,
+            &user,
+            &Bytes::from_slice(&env, colors),
+            &vec![&env, 0, 255, 16777215],
+            &width,
+            &String::from_str(&env, "Hello World"),
+            &String::from_str(&env, "Lorem Ipsum"),
+            &None,
+        )
+    };
+
+    // 0 1 2
+    // 1 1 1
+    assert_eq!(try_mint(&[0, 1, 2, 1, 1, 1], 3), Ok(Ok(1)));
+
+    // ensure every flip and rotation is caught
+    for (colors, width) in [
+        ([2, 1, 0, 1, 1, 1], 3), // horizontal flip
+        ([1, 1, 1, 0, 1, 2], 3), // vertical flip
+        ([1, 0, 1, 1, 1, 2], 2), // 90 degrees
+        ([1, 1, 1, 2, 1, 0], 3), // 180 degrees
+        ([2, 1, 1, 1, 0, 1], 2), // 270 degrees
+    ] {
+        assert_eq!(
+            try_mint(&colors, width),
+            Err(Ok(Error::GlyphSymmetricDuplicate))
+        );
+    }
+
+    // ensure only the transforms in the policy are checked
+    client.glyph_symmetry_policy_set(&SYMMETRY_FLIP_HORIZONTAL);
+
+    assert_eq!(
+        try_mint(&[2, 1, 0, 1, 1, 1], 3),
+        Err(Ok(Error::GlyphSymmetricDuplicate))
+    );
+    assert_eq!(try_mint(&[1, 1, 1, 0, 1, 2], 3), Ok(Ok(2)));
+}
+
+#[test]
+fn test_glyph_encoding_costs() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        contract_id,
+        client,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let user = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&user, &(glyph_fee * 3));
+
+    let mint_colors = |colors: &[u8], legend: &Vec<u32>| {
+        let glyph_index = client.glyph_mint(
+            &user,
+            &user,
+            &user,
+            &Bytes::from_slice(&env, colors),
+            legend,
+            &45,
+            &String::from_str(&env, "Hello World"),
+            &String::from_str(&env, "Lorem Ipsum"),
+            &None,
+        );
+        let write_bytes = env.cost_estimate().resources().write_bytes;
+
+        // ensure the stored encoding decodes back to the minted colors
+        let glyph = client.glyph_get(&glyph_index);
+
+        assert_eq!(glyph.colors, Bytes::from_slice(&env, colors));
+        assert_eq!(glyph.encoding, GlyphEncoding::Raw);
+
+        (glyph_index, write_bytes)
+    };
+
+    // 256 colors, stored raw
+    let mut raw_colors = [0u8; 45 * 45];
+    let mut raw_legend = Vec::new(&env);
+
+    for (index, color) in raw_colors.iter_mut().enumerate() {
+        *color = (index % 256) as u8;
+    }
+
+    for color in 0..256 {
+        raw_legend.push_back(color);
+    }
+
+    let (_, raw_write_bytes) = mint_colors(&raw_colors, &raw_legend);
+
+    // two random colors, bit packed
+    let glyph_index = mint(&env, &client, &contract_id, &user, &user, &user);
+    let packed_write_bytes = env.cost_estimate().resources().write_bytes;
+
+    // two colors in striped rows, run-length encoded
+    let mut striped_colors = [0u8; 45 * 45];
+
+    for (index, color) in striped_colors.iter_mut().enumerate() {
+        *color = (index / 45 % 2) as u8;
+    }
+
+    let (_, striped_write_bytes) = mint_colors(&striped_colors, &vec![&env, 0, 16777215]);
+
+    println!(
+        "write_bytes raw: {}, packed: {}, run-length: {}",
+        raw_write_bytes, packed_write_bytes, striped_write_bytes
+    );
+
+    assert!(packed_write_bytes < raw_write_bytes);
+    assert!(striped_write_bytes < packed_write_bytes);
+
+    env.as_contract(&contract_id, || {
+        let glyph = env
+            .storage()
+            .persistent()
+            .get::<Storage, Glyph>(&Storage::Glyph(glyph_index))
+            .unwrap();
+
+        assert_eq!(glyph.encoding, GlyphEncoding::Packed(1, 45 * 45));
+        assert_eq!(glyph.colors.len(), 254);
+    });
+}
+
+#[test]
+fn test_glyph_mint_tiled() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        client,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let user = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&user, &glyph_fee);
+
+    // a 128x128 checkerboard of 16x16 squares, 4 tiles of 4096 pixels
+    let mut colors = [0u8; 128 * 128];
+
+    for (index, color) in colors.iter_mut().enumerate() {
+        *color = ((index % 128 / 16 + index / 128 / 16) % 2) as u8;
+    }
+
+    let legend = vec![&env, 0, 16777215];
+    let tiles = colors.chunks(4096).collect::<std::vec::Vec<_>>();
+
+    let mut colors_extended_with_width = Bytes::from_slice(&env, &colors);
+
+    colors_extended_with_width.append(&legend.clone().to_xdr(&env));
+    colors_extended_with_width.extend_from_slice(&128u32.to_be_bytes());
+
+    let glyph_hash = env.crypto().sha256(&colors_extended_with_width).to_bytes();
+
+    let begin = |glyph_hash| {
+        client.glyph_mint_begin(
+            &user,
+            &user,
+            &user,
+            &legend,
+            &128,
+            &128,
+            &glyph_hash,
+            &String::from_str(&env, "Hello World"),
+            &String::from_str(&env, "Lorem Ipsum"),
+            &None,
+        )
+    };
+
+    // ensure a session can't finalize against the wrong hash
+    let glyph_index = begin(env.crypto().sha256(&Bytes::new(&env)).to_bytes());
+
+    for (tile, colors) in tiles.iter().enumerate() {
+        client.glyph_mint_tile(
+            &glyph_index,
+            &(tile as u32),
+            &Bytes::from_slice(&env, colors),
+        );
+    }
+
+    let res = client.try_glyph_mint_finalize(&glyph_index);
+
+    assert_eq!(res, Err(Ok(Error::GlyphHashMismatch)));
+
+    client.glyph_mint_cancel(&glyph_index);
+
+    let res = client.try_glyph_mint_finalize(&glyph_index);
+
+    assert_eq!(res, Err(Ok(Error::GlyphMintSessionNotFound)));
+
+    let glyph_index = begin(glyph_hash.clone());
+
+    // ensure tiles can arrive in any order but only once and at the right length
+    client.glyph_mint_tile(&glyph_index, &3, &Bytes::from_slice(&env, tiles[3]));
+
+    let res = client.try_glyph_mint_tile(&glyph_index, &3, &Bytes::from_slice(&env, tiles[3]));
+
+    assert_eq!(res, Err(Ok(Error::GlyphTileInvalid)));
+
+    let res = client.try_glyph_mint_tile(&glyph_index, &4, &Bytes::from_slice(&env, tiles[0]));
+
+    assert_eq!(res, Err(Ok(Error::GlyphTileInvalid)));
+
+    let res =
+        client.try_glyph_mint_tile(&glyph_index, &0, &Bytes::from_slice(&env, &tiles[0][1..]));
+
+    assert_eq!(res, Err(Ok(Error::GlyphTileInvalid)));
+
+    client.glyph_mint_tile(&glyph_index, &0, &Bytes::from_slice(&env, tiles[0]));
+
+    let res = client.try_glyph_mint_finalize(&glyph_index);
+
+    assert_eq!(res, Err(Ok(Error::GlyphTilesMissing)));
+
+    client.glyph_mint_tile(&glyph_index, &2, &Bytes::from_slice(&env, tiles[2]));
+    client.glyph_mint_tile(&glyph_index, &1, &Bytes::from_slice(&env, tiles[1]));
+
+    assert_eq!(client.glyph_mint_finalize(&glyph_index), glyph_index);
+
+    // ensure palette counts are aggregated across tiles
+    let glyph_palette = client.glyph_palette_get(&glyph_index);
+
+    assert_eq!(glyph_palette.pixels, 128 * 128);
+    assert_eq!(
+        glyph_palette.counts,
+        vec![&env, (0, 128 * 64), (16777215, 128 * 64)]
+    );
+
+    let glyph = client.glyph_get(&glyph_index);
+
+    assert_eq!(glyph.encoding, GlyphEncoding::Tiled(4));
+    assert_eq!(glyph.width, 128);
+
+    // ensure tiles page back in order
+    let page = client.glyph_tile_get(&glyph_index, &1, &2);
+
+    assert_eq!(page.len(), 2);
+    assert_eq!(page.get(0), Some(Bytes::from_slice(&env, tiles[1])));
+    assert_eq!(page.get(1), Some(Bytes::from_slice(&env, tiles[2])));
+    assert_eq!(client.glyph_tile_get(&glyph_index, &3, &10).len(), 1);
+
+    // ensure the same image can't be minted again
+    let res = client.try_glyph_mint_begin(
+        &user,
+        &user,
+        &user,
+        &legend,
+        &128,
+        &128,
+        &glyph_hash,
+        &String::from_str(&env, "Hello World"),
+        &String::from_str(&env, "Lorem Ipsum"),
+        &None,
+    );
+
+    assert_eq!(res, Err(Ok(Error::GlyphAlreadyMinted)));
+
+    // ensure a flipped copy is caught once the whole canvas is uploaded
+    client.glyph_symmetry_policy_set(&SYMMETRY_FLIP_HORIZONTAL);
+
+    let mut flipped = [0u8; 128 * 128];
+
+    for (index, color) in flipped.iter_mut().enumerate() {
+        *color = colors[index / 128 * 128 + 127 - index % 128];
+    }
+
+    let mut colors_extended_with_width = Bytes::from_slice(&env, &flipped);
+
+    colors_extended_with_width.append(&legend.clone().to_xdr(&env));
+    colors_extended_with_width.extend_from_slice(&128u32.to_be_bytes());
+
+    let glyph_index = begin(env.crypto().sha256(&colors_extended_with_width).to_bytes());
+
+    for (tile, colors) in flipped.chunks(4096).enumerate() {
+        client.glyph_mint_tile(
+            &glyph_index,
+            &(tile as u32),
+            &Bytes::from_slice(&env, colors),
+        );
+    }
+
+    let res = client.try_glyph_mint_finalize(&glyph_index);
+
+    assert_eq!(res, Err(Ok(Error::GlyphSymmetricDuplicate)));
+}
+
+#[test]
+fn test_glyph_mint_animated() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        contract_id,
+        client,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let user = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&user, &(glyph_fee * 5));
+
+    // a 45x45 blinking pixel over 3 frames
+    let mut frame_1 = [0u8; 45 * 45];
+    let mut frame_2 = [0u8; 45 * 45];
+
+    frame_1[1012] = 1;
+    frame_2[1012] = 2;
+
+    let frames = vec![
+        &env,
+        Bytes::from_array(&env, &frame_1),
+        Bytes::from_array(&env, &frame_2),
+        Bytes::from_array(&env, &frame_1),
+    ];
+    let durations = vec![&env, 100, 50, 100];
+    let legend = vec![&env, 0, 255, 16777215];
+
+    let try_mint = |frames: &Vec<Bytes>, durations: &Vec<u32>| {
+        client.try_glyph_mint_animated(
+            &user,
+            &user,
+            &user,
+            frames,
+            durations,
+            &legend,
+            &45,
+            &String::from_str(&env, "Hello World"),
+            &String::from_str(&env, "Lorem Ipsum"),
+            &None,
+        )
+    };
+
+    let res = try_mint(&frames, &vec![&env, 100, 50]);
+
+    assert_eq!(res, Err(Ok(Error::GlyphFramesInvalid)));
+
+    let res = try_mint(&frames, &vec![&env, 100, 0, 100]);
+
+    assert_eq!(res, Err(Ok(Error::GlyphFramesInvalid)));
+
+    let res = try_mint(
+        &vec![
+            &env,
+            Bytes::from_array(&env, &frame_1),
+            Bytes::from_array(&env, &[0; 45]),
+        ],
+        &vec![&env, 100, 100],
+    );
+
+    assert_eq!(res, Err(Ok(Error::GlyphFramesInvalid)));
+
+    let glyph_index = try_mint(&frames, &durations).unwrap().unwrap();
+
+    // ensure frames decode back with their durations
+    assert_eq!(
+        client.glyph_frames_get(&glyph_index),
+        vec![
+            &env,
+            (100, frames.get_unchecked(0)),
+            (50, frames.get_unchecked(1)),
+            (100, frames.get_unchecked(2)),
+        ]
+    );
+    assert_eq!(
+        client.glyph_get(&glyph_index).colors,
+        frames.get_unchecked(0)
+    );
+
+    // ensure later frames are stored as deltas
+    env.as_contract(&contract_id, || {
+        let animation = env
+            .storage()
+            .persistent()
+            .get::<Storage, GlyphAnimation>(&Storage::GlyphAnimation(glyph_index))
+            .unwrap();
+
+        for frame in animation.frames.iter() {
+            assert_eq!(frame.encoding, GlyphEncoding::Delta);
+            assert_eq!(frame.colors.len(), 3);
+        }
+    });
+
+    // ensure colors are weighted across all frames
+    let glyph_palette = client.glyph_palette_get(&glyph_index);
+
+    assert_eq!(glyph_palette.pixels, 45 * 45 * 3);
+    assert_eq!(
+        glyph_palette.counts,
+        vec![&env, (0, 45 * 45 * 3 - 3), (255, 2), (16777215, 1)]
+    );
+
+    // ensure the animation is hashed over all frames
+    let res = try_mint(&frames, &vec![&env, 200, 200, 200]);
+
+    assert_eq!(res, Err(Ok(Error::GlyphAlreadyMinted)));
+
+    let glyph_index_2 = try_mint(
+        &vec![&env, frames.get_unchecked(1), frames.get_unchecked(0)],
+        &vec![&env, 100, 100],
+    );
+
+    assert_eq!(glyph_index_2, Ok(Ok(glyph_index + 1)));
+
+    // ensure every frame flipped the same way is caught
+    client.glyph_symmetry_policy_set(&SYMMETRY_FLIP_HORIZONTAL);
+
+    let mut frame_3 = [0u8; 45 * 45];
+    let mut frame_4 = [0u8; 45 * 45];
+    let mut frame_3_flipped = [0u8; 45 * 45];
+    let mut frame_4_flipped = [0u8; 45 * 45];
+
+    frame_3[0] = 1;
+    frame_4[0] = 2;
+    frame_3_flipped[44] = 1;
+    frame_4_flipped[44] = 2;
+
+    let glyph_index_3 = try_mint(
+        &vec![
+            &env,
+            Bytes::from_array(&env, &frame_3),
+            Bytes::from_array(&env, &frame_4),
+        ],
+        &vec![&env, 100, 100],
+    );
+
+    assert_eq!(glyph_index_3, Ok(Ok(glyph_index + 2)));
+
+    let res = try_mint(
+        &vec![
+            &env,
+            Bytes::from_array(&env, &frame_3_flipped),
+            Bytes::from_array(&env, &frame_4_flipped),
+        ],
+        &vec![&env, 100, 100],
+    );
+
+    assert_eq!(res, Err(Ok(Error::GlyphSymmetricDuplicate)));
+
+    let glyph_index_4 = try_mint(
+        &vec![
+            &env,
+            Bytes::from_array(&env, &frame_3_flipped),
+            Bytes::from_array(&env, &frame_4),
+        ],
+        &vec![&env, 100, 100],
+    );
+
+    assert_eq!(glyph_index_4, Ok(Ok(glyph_index + 3)));
+
+    // ensure an animation can derive from other glyphs but can't have editions
+    let mint_derived = |max_supply: Option<u32>| {
+        client.try_glyph_mint_animated(
+            &user,
+            &user,
+            &user,
+            &vec![
+                &env,
+                Bytes::from_array(&env, &frame_4),
+                Bytes::from_array(&env, &frame_3),
+            ],
+            &vec![&env, 100, 100],
+            &legend,
+            &45,
+            &String::from_str(&env, "Hello World"),
+            &String::from_str(&env, "Lorem Ipsum"),
+            &Some(GlyphMintOptions {
+                parents: Some(vec![&env, glyph_index, glyph_index + 2]),
+                max_supply,
+                ..Default::default()
+            }),
+        )
+    };
+
+    assert_eq!(mint_derived(Some(2)), Err(Ok(Error::GlyphEditionsInvalid)));
+
+    let glyph_index_5 = mint_derived(None).unwrap().unwrap();
+
+    assert_eq!(
+        client.glyph_lineage_get(&glyph_index_5),
+        (vec![&env, glyph_index, glyph_index + 2], 0)
+    );
+}
+
+#[test]
+fn test_glyph_burn() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        client,
+        contract_id,
+        fee_sac_address,
+        fee_sac_admin_client,
+        fee_sac_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let amount = 100_0000000;
+
+    let user = Address::generate(&env);
+    let bidder_1 = Address::generate(&env);
+    let bidder_2 = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&user, &(glyph_fee * 5));
+    fee_sac_admin_client.mint(&bidder_1, &(amount * 2 + 1));
+    fee_sac_admin_client.mint(&bidder_2, &amount);
+
+    let colors = Bytes::from_array(&env, &[0, 1, 1, 0]);
+    let mint_glyph = |legend: Vec<u32>| {
+        client.try_glyph_mint(
+            &user,
+            &user,
+            &user,
+            &colors,
+            &legend,
+            &2,
+            &String::from_str(&env, "Hello World"),
+            &String::from_str(&env, "Lorem Ipsum"),
+            &None,
+        )
+    };
+
+    let glyph_index = mint_glyph(vec![&env, 0, 16777215]).unwrap().unwrap();
+    let other_glyph_index = mint(&env, &client, &contract_id, &user, &user, &user);
+
+    client.offer_sell_glyph(
+        &glyph_index,
+        &OfferBuy::Asset(fee_sac_address.clone(), amount * 3),
+    );
+    client.offer_sell_glyph(&other_glyph_index, &OfferBuy::Glyph(glyph_index));
+    client.offer_sell_asset(
+        &OfferSellAsset(bidder_1.clone(), fee_sac_address.clone(), amount),
+        &glyph_index,
+    );
+    client.offer_sell_asset(
+        &OfferSellAsset(bidder_1.clone(), fee_sac_address.clone(), amount + 1),
+        &glyph_index,
+    );
+    client.offer_sell_asset(
+        &OfferSellAsset(bidder_2.clone(), fee_sac_address.clone(), amount),
+        &glyph_index,
+    );
+
+    assert_eq!(fee_sac_client.balance(&bidder_1), 0);
+    assert_eq!(fee_sac_client.balance(&bidder_2), 0);
+
+    // ensure a glyph can't be burned out from under its recolors
+    let recolor_index = client.glyph_mint_recolor(
+        &user,
+        &user,
+        &user,
+        &glyph_index,
+        &vec![&env, 16777215, 0],
+        &String::from_str(&env, "Hello World"),
+        &String::from_str(&env, "Lorem Ipsum"),
+        &None,
+    );
+
+    assert_eq!(
+        client.try_glyph_burn(&glyph_index),
+        Err(Ok(Error::GlyphHasDependents))
+    );
+
+    client.glyph_burn(&recolor_index);
+    client.glyph_burn(&glyph_index);
+
+    // ensure every offer for or against the glyph was removed
+    assert_eq!(client.offer_sell_glyph_get(&glyph_index, &None), None);
+    assert_eq!(client.offer_sell_glyph_get(&other_glyph_index, &None), None);
+
+    // ensure escrowed bids were refunded to their bidders
+    assert_eq!(
+        client.offer_sell_asset_get(
+            &OfferSellAssetGet(None, fee_sac_address.clone(), amount),
+            &glyph_index
+        ),
+        None
+    );
+    assert_eq!(
+        client.try_offer_sell_asset_remove(
+            &OfferSellAsset(bidder_1.clone(), fee_sac_address.clone(), amount),
+            &glyph_index,
+        ),
+        Err(Ok(Error::OfferNotFound))
+    );
+    assert_eq!(fee_sac_client.balance(&bidder_1), amount * 2 + 1);
+    assert_eq!(fee_sac_client.balance(&bidder_2), amount);
+    assert_eq!(
+        client.try_offer_sell_asset(
+            &OfferSellAsset(bidder_2.clone(), fee_sac_address.clone(), amount),
+            &glyph_index,
+        ),
+        Err(Ok(Error::GlyphNotMinted))
+    );
+
+    assert_eq!(
+        client.try_glyph_get(&glyph_index),
+        Err(Ok(Error::GlyphNotMinted))
+    );
+    assert_eq!(
+        client.try_glyph_owner_get(&glyph_index),
+        Err(Ok(Error::GlyphNotMinted))
+    );
+    assert_eq!(
+        client.try_glyph_burn(&glyph_index),
+        Err(Ok(Error::GlyphNotMinted))
+    );
+
+    // by default the hash stays taken
+    assert_eq!(
+        mint_glyph(vec![&env, 0, 16777215]),
+        Err(Ok(Error::GlyphAlreadyMinted))
+    );
+
+    client.glyph_burn_frees_hash_set(&true);
+
+    let reminted_index = mint_glyph(vec![&env, 0, 255]).unwrap().unwrap();
+
+    // ensure only an approved spender can burn for the owner
+    let spender = Address::generate(&env);
+
+    assert_eq!(
+        client.try_glyph_burn_from(&spender, &reminted_index),
+        Err(Ok(Error::ApprovalInsufficient))
+    );
+
+    client.approve(&user, &spender, &reminted_index, &200);
+    client.glyph_burn_from(&spender, &reminted_index);
+
+    assert!(mint_glyph(vec![&env, 0, 255]).is_ok());
+}
+
+#[test]
+fn test_glyph_editions() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        client,
+        fee_sac_address,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let amount = 100_0000000;
+
+    let author = Address::generate(&env);
+    let collector = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&author, &(glyph_fee * 5));
+    fee_sac_admin_client.mint(&collector, &amount);
+
+    let colors = Bytes::from_array(&env, &[0, 1, 1, 0]);
+    let mint_glyph = |max_supply: Option<u32>| {
+        client.try_glyph_mint(
+            &author,
+            &author,
+            &author,
+            &colors,
+            &vec![&env, 0, 16777215],
+            &2,
+            &String::from_str(&env, "Hello World"),
+            &String::from_str(&env, "Lorem Ipsum"),
+            &Some(GlyphMintOptions {
+                author_split: None,
+                author_split_immutable: false,
+                author_royalty_rate: None,
+                parents: None,
+                max_supply,
+            }),
+        )
+    };
+
+    assert_eq!(mint_glyph(Some(0)), Err(Ok(Error::GlyphEditionsInvalid)));
+
+    let first_edition = mint_glyph(Some(3)).unwrap().unwrap();
+
+    assert_eq!(client.glyph_get(&first_edition).edition, (1, 3));
+
+    let second_edition = client.glyph_mint_edition(&first_edition, &collector);
+    let third_edition = client.glyph_mint_edition(&first_edition, &author);
+
+    assert_eq!(
+        client.try_glyph_mint_edition(&first_edition, &author),
+        Err(Ok(Error::GlyphEditionsSoldOut))
+    );
+    assert_eq!(
+        client.try_glyph_mint_edition(&second_edition, &author),
+        Err(Ok(Error::GlyphEditionsInvalid))
+    );
+
+    // the pixels are stored once and every edition renders the same image
+    let glyph = client.glyph_get(&second_edition);
+
+    assert_eq!(glyph.colors, colors);
+    assert_eq!(glyph.legend, vec![&env, 0, 16777215]);
+    assert_eq!(glyph.edition, (2, 3));
+    assert_eq!(client.glyph_get(&third_edition).edition, (3, 3));
+
+    env.as_contract(&client.address, || {
+        let stored = env
+            .storage()
+            .persistent()
+            .get::<Storage, Glyph>(&Storage::Glyph(second_edition))
+            .unwrap();
+
+        assert_eq!(stored.colors, Bytes::new(&env));
+        assert_eq!(stored.encoding, GlyphEncoding::Edition(first_edition));
+    });
+
+    // each edition has its own owner but shares the first edition's metadata
+    assert_eq!(client.glyph_owner_get(&second_edition), collector);
+    assert_eq!(client.glyph_owner_get(&third_edition), author);
+
+    client.glyph_metadata_set(
+        &third_edition,
+        &String::from_str(&env, "Editions"),
+        &String::from_str(&env, "Lorem Ipsum"),
+    );
+
+    assert_eq!(
+        client.glyph_metadata_get(&first_edition).title,
+        String::from_str(&env, "Editions")
+    );
+
+    // ensure an edition sells on its own and pays the author
+    client.offer_sell_glyph(
+        &third_edition,
+        &OfferBuy::Asset(fee_sac_address.clone(), amount),
+    );
+    client.offer_sell_asset(
+        &OfferSellAsset(collector.clone(), fee_sac_address.clone(), amount),
+        &third_edition,
+    );
+
+    assert_eq!(client.glyph_owner_get(&third_edition), collector);
+    assert_eq!(client.glyph_owner_get(&first_edition), author);
+    assert_eq!(client.royalties_get(&author, &fee_sac_address), amount);
+
+    // the first edition outlives the rest
+    assert_eq!(
+        client.try_glyph_burn(&first_edition),
+        Err(Ok(Error::GlyphHasDependents))
+    );
+
+    client.glyph_burn(&second_edition);
+
+    // ensure burned editions still count against the supply
+    assert_eq!(
+        client.try_glyph_mint_edition(&first_edition, &author),
+        Err(Ok(Error::GlyphEditionsSoldOut))
+    );
+
+    // ensure recolors have editions too, rendering through the recolor's parent
+    let recolor = client.glyph_mint_recolor(
+        &author,
+        &author,
+        &author,
+        &first_edition,
+        &vec![&env, 16777215, 0],
+        &String::from_str(&env, "Hello World"),
+        &String::from_str(&env, "Lorem Ipsum"),
+        &Some(GlyphMintOptions {
+            max_supply: Some(2),
+            ..Default::default()
+        }),
+    );
+    let recolor_edition = client.glyph_mint_edition(&recolor, &collector);
+    let glyph = client.glyph_get(&recolor_edition);
+
+    assert_eq!(glyph.colors, colors);
+    assert_eq!(glyph.legend, vec![&env, 16777215, 0]);
+    assert_eq!(glyph.edition, (2, 2));
+    assert_eq!(
+        client.try_glyph_mint_edition(&recolor, &author),
+        Err(Ok(Error::GlyphEditionsSoldOut))
+    );
+
+    client.glyph_burn(&recolor_edition);
+    client.glyph_burn(&recolor);
+    client.glyph_burn(&third_edition);
+    client.glyph_burn(&first_edition);
+}
+
+#[test]
+fn test_glyph_upgrade() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        contract_id,
+        client,
+        fee_sac_address,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let amount = 100_0000000;
+
+    let user_1 = Address::generate(&env);
+    let user_2 = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&user_1, &glyph_fee);
+    fee_sac_admin_client.mint(&user_2, &amount);
+
+    let glyph_1_hash = mint(&env, &client, &contract_id, &user_1, &user_1, &user_1);
+    let glyph = client.glyph_get(&glyph_1_hash);
+    let glyph_palette = client.glyph_palette_get(&glyph_1_hash);
+
+    // store the glyph the way it was stored before the upgrade, without a palette
+    env.as_contract(&contract_id, || {
+        env.storage()
+            .persistent()
+            .remove::<Storage>(&Storage::GlyphPalette(glyph_1_hash));
+        env.storage().persistent().set::<Storage, GlyphV1>(
+            &Storage::Glyph(glyph_1_hash),
+            &GlyphV1 {
+                author: glyph.author.clone(),
+                colors: glyph.colors.clone(),
+                legend: glyph.legend.clone(),
+                width: glyph.width,
+            },
+        );
+    });
+
+    // ensure the old glyph still reads and takes the current rates
+    assert_eq!(client.glyph_get(&glyph_1_hash), glyph);
+
+    // ensure the old glyph still sells
+    client.offer_sell_glyph(
+        &glyph_1_hash,
+        &OfferBuy::Asset(fee_sac_address.clone(), amount),
+    );
+    client.offer_sell_asset(
+        &OfferSellAsset(user_2.clone(), fee_sac_address.clone(), amount),
+        &glyph_1_hash,
+    );
+
+    assert_eq!(client.glyph_owner_get(&glyph_1_hash), user_2);
+    assert_eq!(
+        client
+            .sale_quote(&glyph_1_hash, &fee_sac_address, &amount)
+            .author_amounts,
+        vec![&env, (user_1.clone(), 5_0000000)]
+    );
+
+    // ensure the sale rebuilt the palette
+    env.as_contract(&contract_id, || {
+        assert_eq!(
+            env.storage()
+                .persistent()
+                .get::<Storage, GlyphPalette>(&Storage::GlyphPalette(glyph_1_hash)),
+            Some(glyph_palette)
+        );
+    });
+}
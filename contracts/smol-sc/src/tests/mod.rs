@@ -1,6 +1,9 @@
 #![cfg(test)]
 
+mod asset;
 mod color;
 mod glyph;
+mod nft;
 mod offer;
+mod royalties;
 mod utils;
@@ -0,0 +1,453 @@
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    Address, Env, String,
+};
+
+use crate::{
+    tests::utils::{initialize, mint, Init},
+    Error, OfferBuy, OfferSellAsset, Storage,
+};
+
+#[test]
+fn test_nft_interface() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        client,
+        contract_id,
+        fee_sac_address,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let amount = 100_0000000;
+
+    let user_1 = Address::generate(&env);
+    let user_2 = Address::generate(&env);
+    let operator = Address::generate(&env);
+    let spender = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&user_1, &(glyph_fee * 2 + amount));
+
+    let glyph_1 = mint(&env, &client, &contract_id, &user_1, &user_1, &user_1);
+    let glyph_2 = mint(&env, &client, &contract_id, &user_1, &user_1, &user_1);
+
+    assert_eq!(client.name(), String::from_str(&env, "Smol Glyphs"));
+    assert_eq!(client.symbol(), String::from_str(&env, "GLYPH"));
+    assert_eq!(client.token_uri(&glyph_2), String::from_str(&env, "2"));
+
+    client.glyph_base_uri_set(&String::from_str(&env, "ipfs://glyphs/"));
+
+    assert_eq!(
+        client.token_uri(&glyph_2),
+        String::from_str(&env, "ipfs://glyphs/2")
+    );
+    assert_eq!(
+        client.try_token_uri(&(glyph_2 + 1)),
+        Err(Ok(Error::GlyphNotMinted))
+    );
+
+    // ensure both APIs read the same owner and balances follow every transfer path
+    assert_eq!(client.balance(&user_1), 2);
+    assert_eq!(client.owner_of(&glyph_1), client.glyph_owner_get(&glyph_1));
+
+    assert_eq!(
+        client.try_transfer(&user_2, &user_1, &glyph_1),
+        Err(Ok(Error::GlyphOwnerIncorrect))
+    );
+
+    client.transfer(&user_1, &user_2, &glyph_1);
+
+    assert_eq!(client.glyph_owner_get(&glyph_1), user_2);
+    assert_eq!(client.balance(&user_1), 1);
+    assert_eq!(client.balance(&user_2), 1);
+
+    client.glyph_owner_transfer(&glyph_1, &user_1);
+
+    assert_eq!(client.owner_of(&glyph_1), user_1);
+    assert_eq!(client.balance(&user_1), 2);
+    assert_eq!(client.balance(&user_2), 0);
+
+    // a per glyph approval is spent by the transfer
+    assert_eq!(
+        client.try_transfer_from(&spender, &user_1, &user_2, &glyph_1),
+        Err(Ok(Error::ApprovalInsufficient))
+    );
+
+    env.ledger().with_mut(|ledger| ledger.sequence_number = 100);
+
+    assert_eq!(
+        client.try_approve(&user_1, &spender, &glyph_1, &99),
+        Err(Ok(Error::ApprovalExpirationInvalid))
+    );
+
+    client.approve(&user_1, &spender, &glyph_1, &200);
+
+    assert_eq!(client.get_approved(&glyph_1), Some(spender.clone()));
+
+    client.transfer_from(&spender, &user_1, &user_2, &glyph_1);
+
+    assert_eq!(client.owner_of(&glyph_1), user_2);
+    assert_eq!(client.get_approved(&glyph_1), None);
+
+    // operators can approve and transfer every glyph of the owner until they expire
+    client.approve_for_all(&user_1, &operator, &200);
+
+    assert!(client.is_approved_for_all(&user_1, &operator));
+
+    client.approve(&operator, &spender, &glyph_2, &150);
+
+    assert_eq!(client.get_approved(&glyph_2), Some(spender.clone()));
+
+    env.ledger().with_mut(|ledger| ledger.sequence_number = 151);
+
+    assert_eq!(client.get_approved(&glyph_2), None);
+
+    client.transfer_from(&operator, &user_1, &user_2, &glyph_2);
+
+    assert_eq!(client.balance(&user_2), 2);
+
+    client.approve_for_all(&user_2, &operator, &200);
+    client.approve_for_all(&user_2, &operator, &0);
+
+    assert!(!client.is_approved_for_all(&user_2, &operator));
+    assert_eq!(
+        client.try_transfer_from(&operator, &user_2, &user_1, &glyph_2),
+        Err(Ok(Error::ApprovalInsufficient))
+    );
+
+    env.ledger().with_mut(|ledger| ledger.sequence_number = 201);
+
+    assert!(!client.is_approved_for_all(&user_1, &operator));
+
+    // sales and burns keep the balances in step too
+    client.offer_sell_glyph(&glyph_2, &OfferBuy::Asset(fee_sac_address.clone(), amount));
+    client.offer_sell_asset(
+        &OfferSellAsset(user_1.clone(), fee_sac_address.clone(), amount),
+        &glyph_2,
+    );
+
+    assert_eq!(client.owner_of(&glyph_2), user_1);
+    assert_eq!(client.balance(&user_1), 1);
+    assert_eq!(client.balance(&user_2), 1);
+
+    client.glyph_burn(&glyph_2);
+
+    assert_eq!(client.balance(&user_1), 0);
+    assert_eq!(
+        client.try_owner_of(&glyph_2),
+        Err(Ok(Error::GlyphNotMinted))
+    );
+}
+
+#[test]
+fn test_glyph_balances_migrate() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        client,
+        contract_id,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let user_1 = Address::generate(&env);
+    let user_2 = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&user_1, &(glyph_fee * 4));
+
+    let glyph_1 = mint(&env, &client, &contract_id, &user_1, &user_1, &user_1);
+    let glyph_2 = mint(&env, &client, &contract_id, &user_1, &user_1, &user_1);
+    let glyph_3 = mint(&env, &client, &contract_id, &user_1, &user_1, &user_1);
+
+    // as if these glyphs were minted before balances were tracked
+    env.as_contract(&contract_id, || {
+        env.storage()
+            .instance()
+            .remove::<Storage>(&Storage::GlyphBalanceIndex);
+        env.storage()
+            .persistent()
+            .remove::<Storage>(&Storage::GlyphBalance(user_1.clone()));
+    });
+
+    // ensure older glyphs change hands without touching balances until they're counted
+    client.transfer(&user_1, &user_2, &glyph_1);
+
+    assert_eq!(client.balance(&user_1), 0);
+    assert_eq!(client.balance(&user_2), 0);
+
+    let glyph_4 = mint(&env, &client, &contract_id, &user_1, &user_1, &user_1);
+
+    assert_eq!(client.balance(&user_1), 1);
+
+    assert_eq!(client.glyph_balances_migrate(&1), 2);
+    assert_eq!(client.balance(&user_1), 2);
+
+    // counted glyphs are tracked from then on
+    client.transfer(&user_1, &user_2, &glyph_3);
+
+    assert_eq!(client.balance(&user_1), 1);
+    assert_eq!(client.balance(&user_2), 1);
+
+    assert_eq!(client.glyph_balances_migrate(&10), 0);
+    assert_eq!(client.glyph_balances_migrate(&10), 0);
+    assert_eq!(client.balance(&user_1), 2);
+    assert_eq!(client.balance(&user_2), 2);
+
+    client.transfer(&user_2, &user_1, &glyph_1);
+    client.transfer(&user_1, &user_2, &glyph_4);
+
+    assert_eq!(client.owner_of(&glyph_2), user_1);
+    assert_eq!(client.balance(&user_1), 2);
+    assert_eq!(client.balance(&user_2), 2);
+}
+
+#[test]
+fn test_operator_approvals() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        client,
+        contract_id,
+        fee_sac_address,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let owner = Address::generate(&env);
+    let operator = Address::generate(&env);
+    let spender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&owner, &(glyph_fee * 2 + mine_fee));
+
+    let glyph_1 = mint(&env, &client, &contract_id, &owner, &owner, &owner);
+    let glyph_2 = mint(&env, &client, &contract_id, &owner, &owner, &owner);
+
+    client.color_claim(&owner, &owner, &255);
+
+    env.ledger().with_mut(|ledger| ledger.sequence_number = 100);
+
+    // ensure unapproved spenders can't transfer or list
+    assert_eq!(
+        client.try_glyph_owner_transfer_from(&spender, &glyph_1, &receiver),
+        Err(Ok(Error::ApprovalInsufficient))
+    );
+    assert_eq!(
+        client.try_offer_sell_glyph_from(
+            &spender,
+            &glyph_1,
+            &OfferBuy::Asset(fee_sac_address.clone(), 1_0000000)
+        ),
+        Err(Ok(Error::ApprovalInsufficient))
+    );
+    assert_eq!(
+        client.try_color_owner_transfer_from(&spender, &255, &receiver),
+        Err(Ok(Error::ApprovalInsufficient))
+    );
+
+    // a per glyph approval lists and transfers that glyph only, and is cleared by the transfer
+    client.approve(&owner, &spender, &glyph_1, &200);
+
+    assert_eq!(
+        client.glyph_approval_get(&glyph_1),
+        Some((spender.clone(), 200))
+    );
+
+    client.offer_sell_glyph_from(
+        &spender,
+        &glyph_1,
+        &OfferBuy::Asset(fee_sac_address.clone(), 1_0000000),
+    );
+
+    assert!(client.offer_sell_glyph_get(&glyph_1, &None).is_some());
+
+    client.offer_sell_glyph_remove_from(&spender, &glyph_1, &None);
+
+    assert!(client.offer_sell_glyph_get(&glyph_1, &None).is_none());
+    assert_eq!(
+        client.try_glyph_owner_transfer_from(&spender, &glyph_2, &receiver),
+        Err(Ok(Error::ApprovalInsufficient))
+    );
+
+    client.glyph_owner_transfer_from(&spender, &glyph_1, &receiver);
+
+    assert_eq!(client.glyph_owner_get(&glyph_1), receiver);
+    assert_eq!(client.glyph_approval_get(&glyph_1), None);
+
+    // a color approval works the same way
+    client.color_approve(&owner, &spender, &255, &200);
+
+    assert_eq!(
+        client.color_approval_get(&255),
+        Some((spender.clone(), 200))
+    );
+
+    client.color_owner_transfer_from(&spender, &255, &receiver);
+
+    assert_eq!(client.color_owner_get(&255), receiver);
+    assert_eq!(client.color_approval_get(&255), None);
+
+    // operators act for every glyph and color of the owner until they expire
+    client.approve_for_all(&receiver, &operator, &150);
+
+    assert_eq!(
+        client.operator_approval_get(&receiver, &operator),
+        Some(150)
+    );
+
+    client.color_approve(&operator, &spender, &255, &200);
+    client.color_approve(&operator, &spender, &255, &0);
+
+    assert_eq!(client.color_approval_get(&255), None);
+
+    client.glyph_owner_transfer_from(&operator, &glyph_1, &owner);
+
+    assert_eq!(client.glyph_owner_get(&glyph_1), owner);
+
+    env.ledger().with_mut(|ledger| ledger.sequence_number = 151);
+
+    assert_eq!(client.operator_approval_get(&receiver, &operator), None);
+    assert_eq!(
+        client.try_color_owner_transfer_from(&operator, &255, &owner),
+        Err(Ok(Error::ApprovalInsufficient))
+    );
+
+    // the owner can always act without naming a spender
+    client.color_owner_transfer(&255, &owner);
+
+    assert_eq!(client.color_owner_get(&255), owner);
+}
+
+#[test]
+fn test_offer_sell_glyph_lapses() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        client,
+        contract_id,
+        fee_sac_address,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let amount = 100_0000000;
+
+    let owner = Address::generate(&env);
+    let spender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let bidder = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&owner, &(glyph_fee * 2));
+    fee_sac_admin_client.mint(&bidder, &(amount * 4));
+
+    let glyph_1 = mint(&env, &client, &contract_id, &owner, &owner, &owner);
+    let glyph_2 = mint(&env, &client, &contract_id, &owner, &owner, &owner);
+
+    env.ledger().with_mut(|ledger| ledger.sequence_number = 100);
+
+    // ensure a revoked spender's listing is dropped instead of filled
+    client.approve(&owner, &spender, &glyph_1, &200);
+    client.offer_sell_glyph_from(
+        &spender,
+        &glyph_1,
+        &OfferBuy::Asset(fee_sac_address.clone(), amount),
+    );
+    client.approve(&owner, &spender, &glyph_1, &0);
+
+    assert_eq!(
+        client.offer_sell_asset(
+            &OfferSellAsset(bidder.clone(), fee_sac_address.clone(), amount),
+            &glyph_1,
+        ),
+        None
+    );
+    assert_eq!(client.owner_of(&glyph_1), owner);
+    assert!(client.offer_sell_glyph_get(&glyph_1, &None).is_none());
+
+    // ensure an expired spender's listing is dropped the same way
+    client.approve(&owner, &spender, &glyph_1, &200);
+    client.offer_sell_glyph_from(
+        &spender,
+        &glyph_1,
+        &OfferBuy::Asset(fee_sac_address.clone(), amount * 2),
+    );
+
+    env.ledger().with_mut(|ledger| ledger.sequence_number = 201);
+
+    assert_eq!(
+        client.offer_sell_asset(
+            &OfferSellAsset(bidder.clone(), fee_sac_address.clone(), amount * 2),
+            &glyph_1,
+        ),
+        None
+    );
+    assert_eq!(client.owner_of(&glyph_1), owner);
+    assert!(client.offer_sell_glyph_get(&glyph_1, &None).is_none());
+
+    // ensure the owner's listings don't survive a transfer
+    client.offer_sell_glyph(&glyph_2, &OfferBuy::Asset(fee_sac_address.clone(), amount));
+    client.transfer(&owner, &receiver, &glyph_2);
+
+    assert!(client.offer_sell_glyph_get(&glyph_2, &None).is_none());
+    assert_eq!(
+        client.offer_sell_asset(
+            &OfferSellAsset(bidder.clone(), fee_sac_address.clone(), amount),
+            &glyph_2,
+        ),
+        None
+    );
+    assert_eq!(client.owner_of(&glyph_2), receiver);
+}
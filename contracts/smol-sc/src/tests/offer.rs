@@ -1,4 +1,4 @@
-use soroban_sdk::{testutils::Address as _, Address, Env};
+use soroban_sdk::{testutils::Address as _, vec, Address, Bytes, Env, String};
 
 use std::println;
 extern crate std;
@@ -6,9 +6,9 @@ extern crate std;
 use crate::{
     tests::{
         glyph,
-        utils::{initialize, mint, Init},
+        utils::{initialize, mint, Init, TX_MAX_LEDGER_ENTRIES, TX_MAX_WRITE_ENTRIES},
     },
-    OfferBuy, OfferSellAsset, OfferSellAssetGet,
+    Error, OfferBuy, OfferSellAsset, OfferSellAssetGet,
 };
 
 #[test]
@@ -71,6 +71,172 @@ fn test_offer_glyph_for_glyph() {
     assert_eq!(offer_1.is_none(), true);
 }
 
+#[test]
+fn test_offer_glyph_swap_limit() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        contract_id,
+        client,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let user = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&user, &(glyph_fee * 14));
+
+    let wanted = mint(&env, &client, &contract_id, &user, &user, &user);
+
+    // one pixel glyphs in 13 different colors
+    let offered = (0..13)
+        .map(|color| {
+            client.glyph_mint(
+                &user,
+                &user,
+                &user,
+                &Bytes::from_array(&env, &[0]),
+                &vec![&env, color],
+                &1,
+                &String::from_str(&env, "Hello World"),
+                &String::from_str(&env, "Lorem Ipsum"),
+                &None,
+            )
+        })
+        .collect::<std::vec::Vec<_>>();
+
+    for glyph_index in &offered[..12] {
+        client.offer_sell_glyph(glyph_index, &OfferBuy::Glyph(wanted));
+    }
+
+    // ensure the swap offers asking for one glyph are capped
+    assert_eq!(
+        client.try_offer_sell_glyph(&offered[12], &OfferBuy::Glyph(wanted)),
+        Err(Ok(Error::OfferLimitReached))
+    );
+
+    client.offer_sell_glyph_remove(&offered[0], &Some(OfferBuy::Glyph(wanted)));
+    client.offer_sell_glyph(&offered[12], &OfferBuy::Glyph(wanted));
+}
+
+#[test]
+fn test_offer_glyph_bid_limit() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        contract_id,
+        client,
+        fee_sac_address,
+        fee_sac_admin_client,
+        fee_sac_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let amount = 100_0000000;
+
+    let user = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&user, &(glyph_fee * 13));
+
+    let wanted = mint(&env, &client, &contract_id, &user, &user, &user);
+
+    // every distinct bidder and amount is its own escrow
+    let bidders = (0..5)
+        .map(|index| {
+            let bidder = Address::generate(&env);
+
+            fee_sac_admin_client.mint(&bidder, &(amount + index));
+
+            (bidder, amount + index)
+        })
+        .collect::<std::vec::Vec<_>>();
+
+    for (bidder, amount) in &bidders[..4] {
+        client.offer_sell_asset(
+            &OfferSellAsset(bidder.clone(), fee_sac_address.clone(), *amount),
+            &wanted,
+        );
+    }
+
+    // ensure the bids for one glyph are capped
+    let (bidder, amount) = &bidders[4];
+
+    assert_eq!(
+        client.try_offer_sell_asset(
+            &OfferSellAsset(bidder.clone(), fee_sac_address.clone(), *amount),
+            &wanted,
+        ),
+        Err(Ok(Error::OfferLimitReached))
+    );
+
+    client.offer_sell_asset_remove(
+        &OfferSellAsset(bidders[0].0.clone(), fee_sac_address.clone(), bidders[0].1),
+        &wanted,
+    );
+    client.offer_sell_asset(
+        &OfferSellAsset(bidder.clone(), fee_sac_address.clone(), *amount),
+        &wanted,
+    );
+
+    // as many swap offers as a glyph takes
+    for color in 0..12 {
+        let glyph_index = client.glyph_mint(
+            &user,
+            &user,
+            &user,
+            &Bytes::from_array(&env, &[0]),
+            &vec![&env, color],
+            &1,
+            &String::from_str(&env, "Hello World"),
+            &String::from_str(&env, "Lorem Ipsum"),
+            &None,
+        );
+
+        client.offer_sell_glyph(&glyph_index, &OfferBuy::Glyph(wanted));
+    }
+
+    client.glyph_burn(&wanted);
+
+    // ensure burning refunds every bid and fits in one transaction
+    let resources = env.cost_estimate().resources();
+
+    assert!(resources.write_entries <= TX_MAX_WRITE_ENTRIES);
+    assert!(
+        resources.disk_read_entries + resources.memory_read_entries + resources.write_entries
+            <= TX_MAX_LEDGER_ENTRIES
+    );
+
+    for (bidder, amount) in &bidders {
+        assert_eq!(fee_sac_client.balance(bidder), *amount);
+    }
+}
+
 #[test]
 fn test_offer_glyph_for_asset() {
     let env = Env::default();
@@ -321,6 +487,10 @@ fn test_multiple_offers_and_royalties() {
 
     assert_eq!(offer_1.is_none(), true);
 
+    // collect what both sales accrued to the color owner
+    client.color_royalties_collect(&0, &vec![&env, glyph_1_hash], &fee_sac_address);
+    client.color_royalties_collect(&16777215, &vec![&env, glyph_1_hash], &fee_sac_address);
+
     client.royalties_claim(&user_1, &fee_sac_address);
     client.royalties_claim(&user_2, &fee_sac_address);
     client.royalties_claim(&user_3, &fee_sac_address);
@@ -378,7 +548,10 @@ fn test_get_offers() {
     let glyph_1_hash = mint(&env, &client, &contract_id, &user_1, &user_1, &user_1);
     let glyph_2_hash = mint(&env, &client, &contract_id, &user_1, &user_1, &user_1);
 
-    client.offer_sell_glyph(&glyph_1_hash, &OfferBuy::Asset(fee_sac_address.clone(), 0));
+    client.offer_sell_glyph(
+        &glyph_1_hash,
+        &OfferBuy::Asset(fee_sac_address.clone(), 200),
+    );
 
     client.offer_sell_glyph(&glyph_1_hash, &OfferBuy::Glyph(glyph_2_hash.clone()));
 
@@ -390,7 +563,7 @@ fn test_get_offers() {
     let offer_1 = client.offer_sell_glyph_get(&glyph_1_hash, &None);
     let offer_2 = client.offer_sell_glyph_get(
         &glyph_1_hash,
-        &Some(OfferBuy::Asset(fee_sac_address.clone(), 0)),
+        &Some(OfferBuy::Asset(fee_sac_address.clone(), 200)),
     );
 
     let offer_3 = client.offer_sell_glyph_get(&glyph_1_hash, &Some(OfferBuy::Glyph(glyph_2_hash)));
@@ -444,7 +617,10 @@ fn test_remove_offers() {
     let glyph_1_hash = mint(&env, &client, &contract_id, &user_1, &user_1, &user_1);
     let glyph_2_hash = mint(&env, &client, &contract_id, &user_1, &user_1, &user_1);
 
-    client.offer_sell_glyph(&glyph_1_hash, &OfferBuy::Asset(fee_sac_address.clone(), 0));
+    client.offer_sell_glyph(
+        &glyph_1_hash,
+        &OfferBuy::Asset(fee_sac_address.clone(), 200),
+    );
 
     client.offer_sell_glyph(&glyph_1_hash, &OfferBuy::Glyph(glyph_2_hash.clone()));
 
@@ -460,7 +636,7 @@ fn test_remove_offers() {
 
     client.offer_sell_glyph_remove(
         &glyph_1_hash,
-        &Some(OfferBuy::Asset(fee_sac_address.clone(), 0)),
+        &Some(OfferBuy::Asset(fee_sac_address.clone(), 200)),
     );
 
     client.offer_sell_glyph_remove(&glyph_1_hash, &Some(OfferBuy::Glyph(glyph_2_hash.clone())));
@@ -514,13 +690,14 @@ fn test_remove_all_buy_me_now_offers() {
     let user_2 = Address::generate(&env);
 
     fee_sac_admin_client.mint(&user_1, &glyph_fee);
+    fee_sac_admin_client.mint(&user_2, &100);
 
     let glyph_1_hash = mint(&env, &client, &contract_id, &user_1, &user_1, &user_1);
 
     for index in 0..10 {
         client.offer_sell_glyph(
             &glyph_1_hash,
-            &OfferBuy::Asset(fee_sac_address.clone(), index),
+            &OfferBuy::Asset(fee_sac_address.clone(), 100 + index),
         );
     }
 
@@ -529,7 +706,7 @@ fn test_remove_all_buy_me_now_offers() {
     assert_eq!(offers.unwrap(), 10);
 
     client.offer_sell_asset(
-        &OfferSellAsset(user_2, fee_sac_address.clone(), 0),
+        &OfferSellAsset(user_2, fee_sac_address.clone(), 100),
         &glyph_1_hash,
     );
 
@@ -540,7 +717,7 @@ fn test_remove_all_buy_me_now_offers() {
     for index in 0..10 {
         client.offer_sell_glyph(
             &glyph_1_hash,
-            &OfferBuy::Asset(fee_sac_address.clone(), index),
+            &OfferBuy::Asset(fee_sac_address.clone(), 100 + index),
         );
     }
 
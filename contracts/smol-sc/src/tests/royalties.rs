@@ -0,0 +1,2215 @@
+use soroban_sdk::{
+    testutils::{Address as _, MockAuth, MockAuthInvoke},
+    token, vec,
+    xdr::ToXdr,
+    Address, Bytes, Env, IntoVal, String, Val, Vec,
+};
+
+use crate::{
+    tests::utils::{initialize, mint, Init, TX_MAX_LEDGER_ENTRIES, TX_MAX_WRITE_ENTRIES},
+    ContractClient, Error, GlyphLineage, GlyphMintKind, GlyphMintOptions, OfferBuy, OfferSellAsset,
+    RoyaltySplit, Storage, TRANSPARENT_COLOR,
+};
+
+// a 45x45 glyph using every one of its 256 legend colors
+fn mint_256_colors(env: &Env, client: &ContractClient, user: &Address) -> u32 {
+    let mut colors = [0u8; 45 * 45];
+    let mut legend = Vec::new(env);
+
+    for (index, color) in colors.iter_mut().enumerate() {
+        *color = (index % 256) as u8;
+    }
+
+    for color in 0..256 {
+        legend.push_back(color);
+    }
+
+    client.glyph_mint(
+        user,
+        user,
+        user,
+        &Bytes::from_array(env, &colors),
+        &legend,
+        &45,
+        &String::from_str(env, "Hello World"),
+        &String::from_str(env, "Lorem Ipsum"),
+        &None,
+    )
+}
+
+#[test]
+fn test_color_royalties_settle() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        contract_id,
+        client,
+        fee_sac_address,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let amount = 100_0000000;
+
+    let user_1 = Address::generate(&env);
+    let user_2 = Address::generate(&env);
+    let user_3 = Address::generate(&env);
+    let user_4 = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&user_1, &glyph_fee);
+    fee_sac_admin_client.mint(&user_2, &(amount * 2));
+    fee_sac_admin_client.mint(&user_3, &mine_fee);
+
+    client.color_claim(&user_3, &user_3, &0);
+
+    let glyph_1_hash = mint(&env, &client, &contract_id, &user_1, &user_1, &user_1);
+
+    client.offer_sell_glyph(
+        &glyph_1_hash,
+        &OfferBuy::Asset(fee_sac_address.clone(), amount),
+    );
+    client.offer_sell_asset(
+        &OfferSellAsset(user_2.clone(), fee_sac_address.clone(), amount),
+        &glyph_1_hash,
+    );
+
+    // ensure the claimed color accrues its pixel share and the unclaimed one goes to the seller
+    let black_royalties = client.color_royalties_get(&0, &glyph_1_hash, &fee_sac_address);
+
+    assert!(black_royalties > 0 && black_royalties < 2_0000000);
+    assert_eq!(client.royalties_get(&user_3, &fee_sac_address), 0);
+    assert_eq!(
+        client.royalties_get(&user_1, &fee_sac_address),
+        amount - black_royalties
+    );
+    assert_eq!(
+        client.try_color_royalties_collect(&16777215, &vec![&env, glyph_1_hash], &fee_sac_address),
+        Err(Ok(Error::ColorNotClaimed))
+    );
+
+    // ensure a new color owner is paid from the next sale on without inheriting what accrued
+    client.color_owner_transfer(&0, &user_4);
+
+    client.offer_sell_glyph(
+        &glyph_1_hash,
+        &OfferBuy::Asset(fee_sac_address.clone(), amount),
+    );
+    client.offer_sell_asset(
+        &OfferSellAsset(user_2.clone(), fee_sac_address.clone(), amount),
+        &glyph_1_hash,
+    );
+
+    assert_eq!(
+        client.royalties_get(&user_3, &fee_sac_address),
+        black_royalties
+    );
+    assert_eq!(
+        client.color_royalties_collect(&0, &vec![&env, glyph_1_hash], &fee_sac_address),
+        black_royalties
+    );
+    assert_eq!(
+        client.royalties_get(&user_4, &fee_sac_address),
+        black_royalties
+    );
+    assert_eq!(
+        client.try_color_royalties_collect(&0, &vec![&env, glyph_1_hash], &fee_sac_address),
+        Err(Ok(Error::NoRoyaltiesToClaim))
+    );
+}
+
+#[test]
+fn test_color_royalty_max_colors() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        client,
+        fee_sac_address,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let amount = 100_0000000;
+
+    let user_1 = Address::generate(&env);
+    let user_2 = Address::generate(&env);
+    let color_owner = Address::generate(&env);
+    let remainder = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&user_1, &glyph_fee);
+    fee_sac_admin_client.mint(&user_2, &amount);
+    fee_sac_admin_client.mint(&color_owner, &(mine_fee * 2));
+
+    // ensure the royalty palette can't outgrow a sale's ledger entries
+    let res = client.try_color_royalty_max_colors_set(&9);
+
+    assert_eq!(res, Err(Ok(Error::ColorRoyaltyMaxColorsInvalid)));
+
+    client.color_royalty_max_colors_set(&8);
+    client.color_royalty_remainder_set(&remainder);
+
+    client.color_claim(&color_owner, &color_owner, &0);
+    client.color_claim(&color_owner, &color_owner, &8);
+
+    let glyph_1_hash = mint_256_colors(&env, &client, &user_1);
+
+    // ensure only the top 8 colors by pixel count earn royalties
+    let glyph_palette = client.glyph_palette_get(&glyph_1_hash);
+
+    assert_eq!(glyph_palette.pixels, 45 * 45);
+    assert_eq!(glyph_palette.counts.len(), 8);
+    assert_eq!(glyph_palette.counts.get(0), Some((0, 8)));
+    assert_eq!(glyph_palette.counts.get(7), Some((7, 8)));
+
+    client.offer_sell_glyph(
+        &glyph_1_hash,
+        &OfferBuy::Asset(fee_sac_address.clone(), amount),
+    );
+    client.offer_sell_asset(
+        &OfferSellAsset(user_2.clone(), fee_sac_address.clone(), amount),
+        &glyph_1_hash,
+    );
+
+    // ensure the share of the other 248 colors went to the remainder address
+    let color_owner_amount = 2_0000000;
+    let remainder_count = 45 * 45 - 8 * 8;
+
+    assert_eq!(
+        client.royalties_get(&remainder, &fee_sac_address),
+        color_owner_amount * remainder_count / (45 * 45)
+    );
+
+    client.color_royalties_collect(&0, &vec![&env, glyph_1_hash], &fee_sac_address);
+
+    // ensure color 8 is outside the cap and earns nothing
+    assert_eq!(
+        client.try_color_royalties_collect(&8, &vec![&env, glyph_1_hash], &fee_sac_address),
+        Err(Ok(Error::NoRoyaltiesToClaim))
+    );
+    assert_eq!(
+        client.royalties_get(&color_owner, &fee_sac_address),
+        color_owner_amount * 8 / (45 * 45)
+    );
+}
+
+#[test]
+fn test_sale_budget_256_colors() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 1;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        contract_id,
+        client,
+        fee_address,
+        fee_sac_address,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let amount = 100_0000000;
+
+    let user_1 = Address::generate(&env);
+    let user_2 = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&user_1, &(glyph_fee * 3 + mine_fee * 256 + amount * 2));
+    fee_sac_admin_client.mint(&user_2, &(amount * 3));
+
+    let mut color_owners = Vec::new(&env);
+
+    for color in 0..256 {
+        let color_owner = Address::generate(&env);
+
+        client.color_claim(&user_1, &color_owner, &color);
+
+        color_owners.push_back(color_owner);
+    }
+
+    let glyph_1_hash = mint(&env, &client, &contract_id, &user_1, &user_1, &user_1);
+    let glyph_2_hash = mint_256_colors(&env, &client, &user_1);
+    let glyph_3_hash = mint(&env, &client, &contract_id, &user_1, &user_1, &user_1);
+
+    // ensure only the default number of colors earn royalties
+    assert_eq!(client.glyph_palette_get(&glyph_2_hash).counts.len(), 8);
+
+    // warm up the seller's royalties so both measured sales touch the same entries
+    client.offer_sell_glyph(
+        &glyph_3_hash,
+        &OfferBuy::Asset(fee_sac_address.clone(), amount),
+    );
+    client.offer_sell_asset(
+        &OfferSellAsset(user_2.clone(), fee_sac_address.clone(), amount),
+        &glyph_3_hash,
+    );
+
+    client.offer_sell_glyph(
+        &glyph_1_hash,
+        &OfferBuy::Asset(fee_sac_address.clone(), amount),
+    );
+    client.offer_sell_asset(
+        &OfferSellAsset(user_2.clone(), fee_sac_address.clone(), amount),
+        &glyph_1_hash,
+    );
+
+    let resources_2_colors = env.cost_estimate().resources();
+    let color_royalties = |color_owners: &Vec<Address>| {
+        color_owners.iter().fold(
+            client.royalties_get(&fee_address, &fee_sac_address),
+            |color_royalties, color_owner| {
+                color_royalties + client.royalties_get(&color_owner, &fee_sac_address)
+            },
+        )
+    };
+    let color_royalties_2_colors = color_royalties(&color_owners);
+
+    client.offer_sell_glyph(
+        &glyph_2_hash,
+        &OfferBuy::Asset(fee_sac_address.clone(), amount),
+    );
+    client.offer_sell_asset(
+        &OfferSellAsset(user_2.clone(), fee_sac_address.clone(), amount),
+        &glyph_2_hash,
+    );
+
+    let resources_256_colors = env.cost_estimate().resources();
+
+    // ensure a first sale accrues to at most the capped colors no matter how many the glyph uses
+    // each accruing color writes its collected marker, the remainder address its royalties
+    assert!(resources_256_colors.write_entries <= resources_2_colors.write_entries + 8 + 2);
+
+    // ensure the capped colors and the remainder address split the whole color owner royalty
+    for color in 0..8 {
+        client.color_royalties_collect(&color, &vec![&env, glyph_2_hash], &fee_sac_address);
+    }
+
+    let paid = color_royalties(&color_owners) - color_royalties_2_colors;
+
+    assert!(paid <= 2_0000000 && paid > 2_0000000 - 9);
+
+    // ensure later sales only bump the glyph's cumulative royalties whatever its legend
+    client.offer_sell_glyph(
+        &glyph_1_hash,
+        &OfferBuy::Asset(fee_sac_address.clone(), amount),
+    );
+    client.offer_sell_asset(
+        &OfferSellAsset(user_1.clone(), fee_sac_address.clone(), amount),
+        &glyph_1_hash,
+    );
+
+    let resources_2_colors_resale = env.cost_estimate().resources();
+
+    client.offer_sell_glyph(
+        &glyph_2_hash,
+        &OfferBuy::Asset(fee_sac_address.clone(), amount),
+    );
+    client.offer_sell_asset(
+        &OfferSellAsset(user_1.clone(), fee_sac_address.clone(), amount),
+        &glyph_2_hash,
+    );
+
+    let resources_256_colors_resale = env.cost_estimate().resources();
+
+    assert_eq!(
+        resources_256_colors_resale.write_entries,
+        resources_2_colors_resale.write_entries
+    );
+}
+
+#[test]
+fn test_sale_budget_worst_case() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 1;
+    let glyph_fee = 1;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        client,
+        fee_sac_address,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let amount = 100_0000000;
+
+    let source = Address::generate(&env);
+    let seller = Address::generate(&env);
+    let buyer = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&source, &1000_0000000);
+    fee_sac_admin_client.mint(&buyer, &amount);
+
+    client.glyph_lineage_set(&50, &3);
+
+    // every claimed color in the royalty palette has its own owner
+    for color in 0..8 {
+        client.color_claim(&source, &Address::generate(&env), &color);
+    }
+
+    // a full tree of distinct authors each with a full split, 4 parents per glyph as deep as
+    // royalties cascade
+    let mint_glyph = |colors: Bytes, legend: Vec<u32>, width: u32, parents: Vec<u32>| {
+        let glyph_index = client.glyph_mint(
+            &source,
+            &Address::generate(&env),
+            &seller,
+            &colors,
+            &legend,
+            &width,
+            &String::from_str(&env, "Hello World"),
+            &String::from_str(&env, "Lorem Ipsum"),
+            &Some(GlyphMintOptions {
+                author_split: None,
+                author_split_immutable: false,
+                author_royalty_rate: None,
+                parents: if parents.is_empty() {
+                    None
+                } else {
+                    Some(parents)
+                },
+                max_supply: None,
+            }),
+        );
+        let mut recipients = Vec::new(&env);
+
+        for _ in 0..4 {
+            recipients.push_back((Address::generate(&env), 2_500));
+        }
+
+        client.glyph_author_split_set(
+            &glyph_index,
+            &Some(RoyaltySplit {
+                recipients,
+                immutable: false,
+            }),
+        );
+
+        glyph_index
+    };
+    let mut level = Vec::new(&env);
+    let mut next_color = 0x100;
+
+    for depth in 0..3 {
+        let mut next_level = Vec::new(&env);
+
+        for index in 0..(64 >> (depth * 2)) {
+            let parents = if depth == 0 {
+                Vec::new(&env)
+            } else {
+                level.slice(index * 4..index * 4 + 4)
+            };
+
+            // a new legend color makes every glyph unique
+            next_level.push_back(mint_glyph(
+                Bytes::from_array(&env, &[0, 1, 1, 0]),
+                vec![&env, 0, next_color],
+                2,
+                parents,
+            ));
+
+            next_color += 1;
+        }
+
+        level = next_level;
+    }
+
+    let mut colors = [0u8; 45 * 45];
+    let mut legend = Vec::new(&env);
+
+    for (index, color) in colors.iter_mut().enumerate() {
+        *color = (index % 256) as u8;
+    }
+
+    for color in 0..256 {
+        legend.push_back(color);
+    }
+
+    let glyph_index = mint_glyph(Bytes::from_array(&env, &colors), legend, 45, level);
+
+    // ensure the split is paid in full and ancestors only while recipients fit the sale
+    assert_eq!(
+        client
+            .sale_quote(&glyph_index, &fee_sac_address, &amount)
+            .author_amounts
+            .len(),
+        8
+    );
+
+    client.offer_sell_glyph(
+        &glyph_index,
+        &OfferBuy::Asset(fee_sac_address.clone(), amount),
+    );
+    client.offer_sell_asset(
+        &OfferSellAsset(buyer.clone(), fee_sac_address.clone(), amount),
+        &glyph_index,
+    );
+
+    let resources = env.cost_estimate().resources();
+
+    // ensure the first sale of the widest lineage and palette fits in one transaction
+    assert!(resources.write_entries <= TX_MAX_WRITE_ENTRIES);
+    assert!(
+        resources.disk_read_entries + resources.memory_read_entries + resources.write_entries
+            <= TX_MAX_LEDGER_ENTRIES
+    );
+}
+
+#[test]
+fn test_royalty_conservation() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        contract_id,
+        client,
+        fee_address,
+        fee_sac_address,
+        fee_sac_admin_client,
+        fee_sac_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let users = vec![
+        &env,
+        Address::generate(&env),
+        Address::generate(&env),
+        Address::generate(&env),
+    ];
+    let bidder = Address::generate(&env);
+    let user_1 = users.get_unchecked(0);
+
+    fee_sac_admin_client.mint(&user_1, &(glyph_fee * 2 + mine_fee));
+
+    // route most of the color owner royalties through the remainder address too
+    client.color_royalty_max_colors_set(&1);
+
+    client.color_claim(&user_1, &user_1, &0);
+
+    let glyphs = vec![
+        &env,
+        mint(&env, &client, &contract_id, &user_1, &user_1, &user_1),
+        mint_256_colors(&env, &client, &user_1),
+    ];
+
+    // keep an escrowed bid open for the whole run
+    let bid_amount = 10000_0000000;
+
+    fee_sac_admin_client.mint(&bidder, &bid_amount);
+
+    client.offer_sell_asset(
+        &OfferSellAsset(bidder.clone(), fee_sac_address.clone(), bid_amount),
+        &glyphs.get_unchecked(0),
+    );
+
+    for round in 0..40u32 {
+        let glyph_index = glyphs.get_unchecked(round % glyphs.len());
+        let seller = client.glyph_owner_get(&glyph_index);
+        let buyer = users.get_unchecked((round * 7 + 1) % users.len());
+        let buyer = if buyer == seller {
+            users.get_unchecked((round * 7 + 2) % users.len())
+        } else {
+            buyer
+        };
+
+        let amount = env.as_contract(&contract_id, || {
+            env.prng().gen_range::<u64>(50..1000_0000000) as i128
+        });
+
+        fee_sac_admin_client.mint(&buyer, &amount);
+
+        client.offer_sell_glyph(
+            &glyph_index,
+            &OfferBuy::Asset(fee_sac_address.clone(), amount),
+        );
+        client.offer_sell_asset(
+            &OfferSellAsset(buyer.clone(), fee_sac_address.clone(), amount),
+            &glyph_index,
+        );
+
+        if round % 5 == 4 {
+            let _ = client.try_royalties_claim(&seller, &fee_sac_address);
+        }
+
+        // ensure the contract holds exactly what it owes
+        let mut liabilities = bid_amount;
+
+        for user in users.iter() {
+            liabilities += client.royalties_get(&user, &fee_sac_address);
+        }
+
+        liabilities += client.royalties_get(&fee_address, &fee_sac_address);
+
+        for glyph_index in glyphs.iter() {
+            liabilities += client.color_royalties_get(&0, &glyph_index, &fee_sac_address);
+        }
+
+        assert_eq!(fee_sac_client.balance(&contract_id), liabilities);
+    }
+}
+
+#[test]
+fn test_sale_amount_too_low() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        contract_id,
+        client,
+        fee_sac_address,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let user_1 = Address::generate(&env);
+    let user_2 = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&user_1, &glyph_fee);
+    fee_sac_admin_client.mint(&user_2, &50);
+
+    let glyph_1_hash = mint(&env, &client, &contract_id, &user_1, &user_1, &user_1);
+
+    // 2% of 49 rounds down to nothing for the color owners
+    let res =
+        client.try_offer_sell_glyph(&glyph_1_hash, &OfferBuy::Asset(fee_sac_address.clone(), 49));
+
+    assert_eq!(res, Err(Ok(Error::SaleAmountTooLow)));
+
+    let res = client.try_offer_sell_asset(
+        &OfferSellAsset(user_2.clone(), fee_sac_address.clone(), 49),
+        &glyph_1_hash,
+    );
+
+    assert_eq!(res, Err(Ok(Error::SaleAmountTooLow)));
+
+    // ensure the smallest sale pays every share exactly
+    client.offer_sell_glyph(&glyph_1_hash, &OfferBuy::Asset(fee_sac_address.clone(), 50));
+    client.offer_sell_asset(
+        &OfferSellAsset(user_2.clone(), fee_sac_address.clone(), 50),
+        &glyph_1_hash,
+    );
+
+    // the unclaimed colors' share goes back to user 1 as the seller
+    assert_eq!(client.royalties_get(&user_1, &fee_sac_address), 50);
+
+    // ensure royalty rates can't exceed the sale amount
+    let res = client.try_update(&None, &None, &None, &None, &None, &Some(50), &Some(51));
+
+    assert_eq!(res, Err(Ok(Error::RoyaltyRateOutOfRange)));
+}
+
+#[test]
+fn test_royalties_claim_batch() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        admin,
+        contract_id,
+        client,
+        fee_sac_address,
+        fee_sac_admin_client,
+        fee_sac_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let amount = 100_0000000;
+
+    let user_1 = Address::generate(&env);
+    let user_2 = Address::generate(&env);
+    let recipient = Address::generate(&env);
+
+    let other_sac = env.register_stellar_asset_contract_v2(admin.clone());
+    let other_sac_address = other_sac.address();
+    let other_sac_admin_client = token::StellarAssetClient::new(&env, &other_sac_address);
+    let other_sac_client = token::TokenClient::new(&env, &other_sac_address);
+
+    client.asset_allow(&other_sac_address, &None);
+
+    fee_sac_admin_client.mint(&user_1, &glyph_fee);
+    fee_sac_admin_client.mint(&user_2, &amount);
+    other_sac_admin_client.mint(&user_1, &amount);
+
+    let glyph_1_hash = mint(&env, &client, &contract_id, &user_1, &user_1, &user_1);
+
+    // earn royalties in two assets
+    client.offer_sell_glyph(
+        &glyph_1_hash,
+        &OfferBuy::Asset(fee_sac_address.clone(), amount),
+    );
+    client.offer_sell_asset(
+        &OfferSellAsset(user_2.clone(), fee_sac_address.clone(), amount),
+        &glyph_1_hash,
+    );
+    client.offer_sell_glyph(
+        &glyph_1_hash,
+        &OfferBuy::Asset(other_sac_address.clone(), amount),
+    );
+    client.offer_sell_asset(
+        &OfferSellAsset(user_1.clone(), other_sac_address.clone(), amount),
+        &glyph_1_hash,
+    );
+
+    // ensure both assets are discoverable
+    let royalties_assets = client.royalties_assets_get(&user_1);
+
+    assert_eq!(royalties_assets.len(), 2);
+    assert!(royalties_assets.contains(&fee_sac_address));
+    assert!(royalties_assets.contains(&other_sac_address));
+
+    let fee_sac_royalties = client.royalties_get(&user_1, &fee_sac_address);
+    let other_sac_royalties = client.royalties_get(&user_1, &other_sac_address);
+
+    // claim everything to another address
+    let claimed = client.royalties_claim_batch(&user_1, &None, &Some(recipient.clone()));
+
+    assert_eq!(claimed.len(), 2);
+    assert_eq!(
+        claimed.get(fee_sac_address.clone()),
+        Some(fee_sac_royalties)
+    );
+    assert_eq!(
+        claimed.get(other_sac_address.clone()),
+        Some(other_sac_royalties)
+    );
+    assert_eq!(fee_sac_client.balance(&recipient), fee_sac_royalties);
+    assert_eq!(other_sac_client.balance(&recipient), other_sac_royalties);
+    assert_eq!(client.royalties_assets_get(&user_1).len(), 0);
+
+    // ensure empty balances are skipped
+    let claimed = client.royalties_claim_batch(
+        &user_1,
+        &Some(vec![
+            &env,
+            fee_sac_address.clone(),
+            other_sac_address.clone(),
+        ]),
+        &None,
+    );
+
+    assert_eq!(claimed.len(), 0);
+}
+
+#[test]
+fn test_glyph_author_split() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        contract_id,
+        client,
+        fee_sac_address,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let amount = 100_0000003;
+
+    let user_1 = Address::generate(&env);
+    let user_2 = Address::generate(&env);
+    let collaborator_1 = Address::generate(&env);
+    let collaborator_2 = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&user_1, &glyph_fee);
+    fee_sac_admin_client.mint(&user_2, &amount);
+
+    let glyph_1_hash = mint(&env, &client, &contract_id, &user_1, &user_1, &user_1);
+
+    assert_eq!(client.glyph_author_split_get(&glyph_1_hash), None);
+
+    // ensure splits must add up to 100%
+    let res = client.try_glyph_author_split_set(
+        &glyph_1_hash,
+        &Some(RoyaltySplit {
+            recipients: vec![
+                &env,
+                (collaborator_1.clone(), 5_000),
+                (collaborator_2.clone(), 4_999),
+            ],
+            immutable: false,
+        }),
+    );
+
+    assert_eq!(res, Err(Ok(Error::AuthorSplitInvalid)));
+
+    // ensure splits are capped at 4 recipients
+    let mut recipients = Vec::new(&env);
+
+    for _ in 0..5 {
+        recipients.push_back((Address::generate(&env), 2_000));
+    }
+
+    let res = client.try_glyph_author_split_set(
+        &glyph_1_hash,
+        &Some(RoyaltySplit {
+            recipients,
+            immutable: false,
+        }),
+    );
+
+    assert_eq!(res, Err(Ok(Error::AuthorSplitInvalid)));
+
+    let author_split = RoyaltySplit {
+        recipients: vec![
+            &env,
+            (collaborator_1.clone(), 6_667),
+            (collaborator_2.clone(), 3_333),
+        ],
+        immutable: true,
+    };
+
+    client.glyph_author_split_set(&glyph_1_hash, &Some(author_split.clone()));
+
+    assert_eq!(
+        client.glyph_author_split_get(&glyph_1_hash),
+        Some(author_split)
+    );
+
+    // ensure immutable splits stay put
+    let res = client.try_glyph_author_split_set(&glyph_1_hash, &None);
+
+    assert_eq!(res, Err(Ok(Error::AuthorSplitImmutable)));
+
+    client.offer_sell_glyph(
+        &glyph_1_hash,
+        &OfferBuy::Asset(fee_sac_address.clone(), amount),
+    );
+    client.offer_sell_asset(
+        &OfferSellAsset(user_2.clone(), fee_sac_address.clone(), amount),
+        &glyph_1_hash,
+    );
+
+    // ensure the author royalty is fanned out exactly
+    let author_amount = amount * 5 / 100;
+    let collaborator_2_amount = author_amount * 3_333 / 10_000;
+
+    assert_eq!(
+        client.royalties_get(&collaborator_2, &fee_sac_address),
+        collaborator_2_amount
+    );
+    assert_eq!(
+        client.royalties_get(&collaborator_1, &fee_sac_address),
+        author_amount - collaborator_2_amount
+    );
+    assert_eq!(
+        client.royalties_get(&user_1, &fee_sac_address),
+        amount - author_amount
+    );
+}
+
+#[test]
+fn test_glyph_author_split_auth() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        contract_id,
+        client,
+        fee_address,
+        fee_sac_address,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let source = Address::generate(&env);
+    let author = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&source, &glyph_fee);
+
+    let colors = Bytes::from_array(&env, &[0, 1, 1, 0]);
+    let legend = vec![&env, 0, 16777215];
+    let title = String::from_str(&env, "Hello World");
+    let story = String::from_str(&env, "Lorem Ipsum");
+    let options = Some(GlyphMintOptions {
+        author_split: Some(vec![&env, (source.clone(), 10_000)]),
+        ..Default::default()
+    });
+    let glyph_mint_args: Vec<Val> = (
+        source.clone(),
+        author.clone(),
+        source.clone(),
+        colors.clone(),
+        legend.clone(),
+        2u32,
+        title.clone(),
+        story.clone(),
+        options.clone(),
+    )
+        .into_val(&env);
+    let pay_fee_invoke = MockAuthInvoke {
+        contract: &fee_sac_address,
+        fn_name: "transfer",
+        args: (source.clone(), fee_address.clone(), glyph_fee).into_val(&env),
+        sub_invokes: &[],
+    };
+    let source_auth = MockAuth {
+        address: &source,
+        invoke: &MockAuthInvoke {
+            contract: &contract_id,
+            fn_name: "glyph_mint",
+            args: glyph_mint_args.clone(),
+            sub_invokes: &[pay_fee_invoke],
+        },
+    };
+    let author_auth = MockAuth {
+        address: &author,
+        invoke: &MockAuthInvoke {
+            contract: &contract_id,
+            fn_name: "glyph_mint",
+            args: glyph_mint_args,
+            sub_invokes: &[],
+        },
+    };
+
+    // ensure the source can't redirect someone else's author royalties
+    let res = client
+        .mock_auths(core::slice::from_ref(&source_auth))
+        .try_glyph_mint(
+            &source, &author, &source, &colors, &legend, &2, &title, &story, &options,
+        );
+
+    assert!(res.is_err());
+
+    // ensure the split is accepted once the author signs too
+    let glyph_index = client.mock_auths(&[source_auth, author_auth]).glyph_mint(
+        &source, &author, &source, &colors, &legend, &2, &title, &story, &options,
+    );
+
+    assert_eq!(
+        client.glyph_author_split_get(&glyph_index),
+        Some(RoyaltySplit {
+            recipients: vec![&env, (source.clone(), 10_000)],
+            immutable: false,
+        })
+    );
+}
+
+#[test]
+fn test_glyph_author_royalty_rate_auth() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        contract_id,
+        client,
+        fee_address,
+        fee_sac_address,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let source = Address::generate(&env);
+    let author = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&source, &glyph_fee);
+
+    client.glyph_author_royalty_bounds_set(&0, &20);
+
+    let colors = Bytes::from_array(&env, &[0, 1, 1, 0]);
+    let legend = vec![&env, 0, 16777215];
+    let title = String::from_str(&env, "Hello World");
+    let story = String::from_str(&env, "Lorem Ipsum");
+    let options = Some(GlyphMintOptions {
+        author_split: None,
+        author_split_immutable: false,
+        author_royalty_rate: Some(0),
+        parents: None,
+        max_supply: None,
+    });
+    let glyph_mint_args: Vec<Val> = (
+        source.clone(),
+        author.clone(),
+        source.clone(),
+        colors.clone(),
+        legend.clone(),
+        2u32,
+        title.clone(),
+        story.clone(),
+        options.clone(),
+    )
+        .into_val(&env);
+    let pay_fee_invoke = MockAuthInvoke {
+        contract: &fee_sac_address,
+        fn_name: "transfer",
+        args: (source.clone(), fee_address.clone(), glyph_fee).into_val(&env),
+        sub_invokes: &[],
+    };
+    let source_auth = MockAuth {
+        address: &source,
+        invoke: &MockAuthInvoke {
+            contract: &contract_id,
+            fn_name: "glyph_mint",
+            args: glyph_mint_args.clone(),
+            sub_invokes: &[pay_fee_invoke],
+        },
+    };
+    let author_auth = MockAuth {
+        address: &author,
+        invoke: &MockAuthInvoke {
+            contract: &contract_id,
+            fn_name: "glyph_mint",
+            args: glyph_mint_args,
+            sub_invokes: &[],
+        },
+    };
+
+    // ensure the source can't zero out someone else's author royalty
+    let res = client
+        .mock_auths(core::slice::from_ref(&source_auth))
+        .try_glyph_mint(
+            &source, &author, &source, &colors, &legend, &2, &title, &story, &options,
+        );
+
+    assert!(res.is_err());
+
+    // ensure the rate is accepted once the author signs too
+    let glyph_index = client.mock_auths(&[source_auth, author_auth]).glyph_mint(
+        &source, &author, &source, &colors, &legend, &2, &title, &story, &options,
+    );
+
+    assert_eq!(client.glyph_get(&glyph_index).author_royalty_rate, 0);
+}
+
+#[test]
+fn test_royalty_rates_snapshot() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        contract_id,
+        client,
+        fee_sac_address,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let amount = 100_0000000;
+
+    let user_1 = Address::generate(&env);
+    let user_2 = Address::generate(&env);
+    let color_owner = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&user_1, &(glyph_fee * 2));
+    fee_sac_admin_client.mint(&user_2, &amount);
+    fee_sac_admin_client.mint(&color_owner, &(mine_fee * 2));
+
+    client.color_claim(&color_owner, &color_owner, &0);
+    client.color_claim(&color_owner, &color_owner, &16777215);
+
+    let glyph_1_hash = mint(&env, &client, &contract_id, &user_1, &user_1, &user_1);
+
+    // raise the rates and let authors pick between 1% and 20%
+    client.update(&None, &None, &None, &None, &None, &Some(1), &Some(10));
+    client.glyph_author_royalty_bounds_set(&1, &20);
+
+    let glyph = client.glyph_get(&glyph_1_hash);
+
+    assert_eq!(glyph.author_royalty_rate, 5);
+    assert_eq!(glyph.color_owner_royalty_rate, 2);
+
+    // ensure the sale settles with the rates from mint
+    client.offer_sell_glyph(
+        &glyph_1_hash,
+        &OfferBuy::Asset(fee_sac_address.clone(), amount),
+    );
+    client.offer_sell_asset(
+        &OfferSellAsset(user_2.clone(), fee_sac_address.clone(), amount),
+        &glyph_1_hash,
+    );
+
+    client.color_royalties_collect(&0, &vec![&env, glyph_1_hash], &fee_sac_address);
+    client.color_royalties_collect(&16777215, &vec![&env, glyph_1_hash], &fee_sac_address);
+
+    let color_owner_royalties = client.royalties_get(&color_owner, &fee_sac_address);
+
+    assert!((2_0000000 - 1..=2_0000000).contains(&color_owner_royalties));
+    assert_eq!(
+        client.royalties_get(&user_1, &fee_sac_address),
+        amount - color_owner_royalties
+    );
+
+    // ensure authors can only pick a rate within the bounds
+    let colors = Bytes::from_array(&env, &[0, 1, 1, 0]);
+    let legend = vec![&env, 0, 16777215];
+    let title = String::from_str(&env, "Hello World");
+    let story = String::from_str(&env, "Lorem Ipsum");
+
+    let res = client.try_glyph_mint(
+        &user_1,
+        &user_1,
+        &user_1,
+        &colors,
+        &legend,
+        &2,
+        &title,
+        &story,
+        &Some(GlyphMintOptions {
+            author_split: None,
+            author_split_immutable: false,
+            author_royalty_rate: Some(21),
+            parents: None,
+            max_supply: None,
+        }),
+    );
+
+    assert_eq!(res, Err(Ok(Error::RoyaltyRateOutOfRange)));
+
+    let glyph_2_hash = client.glyph_mint(
+        &user_1,
+        &user_1,
+        &user_1,
+        &colors,
+        &legend,
+        &2,
+        &title,
+        &story,
+        &Some(GlyphMintOptions {
+            author_split: None,
+            author_split_immutable: false,
+            author_royalty_rate: Some(15),
+            parents: None,
+            max_supply: None,
+        }),
+    );
+
+    let glyph = client.glyph_get(&glyph_2_hash);
+
+    assert_eq!(glyph.author_royalty_rate, 15);
+    assert_eq!(glyph.color_owner_royalty_rate, 1);
+}
+
+#[test]
+fn test_glyph_mine_color_fee() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_fee = 10_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        client,
+        fee_address,
+        fee_sac_address,
+        fee_sac_client,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let user_1 = Address::generate(&env);
+    let user_2 = Address::generate(&env);
+    let remainder = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&user_1, &(mine_fee * 2));
+    fee_sac_admin_client.mint(&user_2, &(glyph_fee + color_fee));
+
+    client.color_royalty_max_colors_set(&8);
+    client.color_royalty_remainder_set(&remainder);
+    client.glyph_mine_color_fee_set(&color_fee);
+
+    client.color_claim(&user_1, &user_1, &0);
+    client.color_claim(&user_1, &user_1, &7);
+
+    let mut colors = [0u8; 45 * 45];
+    let mut legend = Vec::new(&env);
+
+    for (index, color) in colors.iter_mut().enumerate() {
+        *color = (index % 256) as u8;
+    }
+
+    for color in 0..256 {
+        legend.push_back(color);
+    }
+
+    // ensure the quote only charges the claimed colors in the top 8 and the remainder
+    let quote = client.glyph_mint_quote(&GlyphMintKind::Raw(
+        Bytes::from_array(&env, &colors),
+        legend,
+    ));
+    let color_amount = color_fee * 8 / (45 * 45);
+
+    assert_eq!(quote.fee_sac, fee_sac_address);
+    assert_eq!(quote.mine_fee, glyph_fee);
+    assert_eq!(quote.color_fees.len(), 8);
+    assert_eq!(quote.color_fees.get(0), Some((0, 8, color_amount)));
+    assert_eq!(quote.color_fees.get(1), Some((1, 8, 0)));
+    assert_eq!(quote.color_fee, color_amount * 2 + quote.remainder_fee);
+
+    let glyph_1_hash = mint_256_colors(&env, &client, &user_2);
+
+    client.color_royalties_collect(&0, &vec![&env, glyph_1_hash], &fee_sac_address);
+    client.color_royalties_collect(&7, &vec![&env, glyph_1_hash], &fee_sac_address);
+
+    // ensure the minter wasn't charged for the unclaimed colors
+    assert_eq!(fee_sac_client.balance(&user_2), color_fee - quote.color_fee);
+    assert_eq!(
+        client.royalties_get(&remainder, &fee_sac_address),
+        quote.remainder_fee
+    );
+    assert_eq!(
+        client.royalties_get(&user_1, &fee_sac_address),
+        color_amount * 2
+    );
+    assert_eq!(client.royalties_get(&fee_address, &fee_sac_address), 0);
+
+    // ensure a glyph using only unclaimed colors pays no color fee at all
+    let user_3 = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&user_3, &glyph_fee);
+
+    client.glyph_mint(
+        &user_3,
+        &user_3,
+        &user_3,
+        &Bytes::from_array(&env, &[0, 1, 1, 0]),
+        &vec![&env, 8, 9],
+        &2,
+        &String::from_str(&env, "Hello World"),
+        &String::from_str(&env, "Lorem Ipsum"),
+        &None,
+    );
+
+    assert_eq!(fee_sac_client.balance(&user_3), 0);
+}
+
+#[test]
+fn test_glyph_mint_quote_kinds() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_fee = 10_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        client,
+        fee_sac_client,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let user_1 = Address::generate(&env);
+    let user_2 = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&user_1, &(mine_fee * 2));
+
+    client.glyph_mine_color_fee_set(&color_fee);
+
+    client.color_claim(&user_1, &user_1, &0);
+    client.color_claim(&user_1, &user_1, &7);
+
+    let title = String::from_str(&env, "Hello World");
+    let story = String::from_str(&env, "Lorem Ipsum");
+    let colors = Bytes::from_array(&env, &[0, 1, 1, 0]);
+    let legend = vec![&env, 0, 7];
+
+    // fund exactly what each quote asks for so any difference in the charge fails the mint
+    let fund = |kind: GlyphMintKind| {
+        let quote = client.glyph_mint_quote(&kind);
+
+        fee_sac_admin_client.mint(&user_2, &(glyph_fee + quote.color_fee));
+
+        quote
+    };
+
+    let quote = fund(GlyphMintKind::Raw(colors.clone(), legend.clone()));
+
+    assert_eq!(quote.color_fee, color_fee);
+
+    let glyph_index = client.glyph_mint(
+        &user_2,
+        &user_2,
+        &user_2,
+        &colors,
+        &legend,
+        &2,
+        &title,
+        &story,
+        &Some(GlyphMintOptions {
+            max_supply: Some(3),
+            ..Default::default()
+        }),
+    );
+
+    assert_eq!(fee_sac_client.balance(&user_2), 0);
+
+    // ensure a recolor is quoted by its parent's pixels under the new legend
+    let quote = fund(GlyphMintKind::Recolor(glyph_index, vec![&env, 7, 9]));
+
+    assert_eq!(quote.color_fee, color_fee / 2);
+
+    client.glyph_mint_recolor(
+        &user_2,
+        &user_2,
+        &user_2,
+        &glyph_index,
+        &vec![&env, 7, 9],
+        &title,
+        &story,
+        &None,
+    );
+
+    assert_eq!(fee_sac_client.balance(&user_2), 0);
+
+    // ensure a composite is quoted by its flattened image
+    let layers = vec![&env, (glyph_index, 0, 0, 0), (glyph_index, 1, 1, 1)];
+    let quote = fund(GlyphMintKind::Composite(layers.clone(), 3, 3));
+
+    assert_eq!(quote.color_fees.len(), 2);
+
+    client.glyph_mint_composite(
+        &user_2, &user_2, &user_2, &layers, &3, &3, &title, &story, &None,
+    );
+
+    assert_eq!(fee_sac_client.balance(&user_2), 0);
+
+    // ensure an animation is quoted across all of its frames
+    let frames = vec![&env, colors.clone(), Bytes::from_array(&env, &[1, 0, 0, 1])];
+    let quote = fund(GlyphMintKind::Animated(frames.clone(), legend.clone(), 2));
+
+    assert_eq!(quote.color_fee, color_fee);
+
+    client.glyph_mint_animated(
+        &user_2,
+        &user_2,
+        &user_2,
+        &frames,
+        &vec![&env, 100, 100],
+        &legend,
+        &2,
+        &title,
+        &story,
+        &None,
+    );
+
+    assert_eq!(fee_sac_client.balance(&user_2), 0);
+
+    // ensure an edition is quoted by its first edition's palette
+    let quote = fund(GlyphMintKind::Edition(glyph_index));
+
+    assert_eq!(quote.color_fee, color_fee);
+
+    let edition_index = client.glyph_mint_edition(&glyph_index, &user_2);
+
+    assert_eq!(fee_sac_client.balance(&user_2), 0);
+
+    let res = client.try_glyph_mint_quote(&GlyphMintKind::Edition(edition_index));
+
+    assert_eq!(res, Err(Ok(Error::GlyphEditionsInvalid)));
+
+    // ensure a tiled glyph is quoted once its tiles are uploaded
+    let tile_colors = Bytes::from_array(&env, &[0, 0, 0, 1]);
+    let mut colors_extended_with_width = tile_colors.clone();
+
+    colors_extended_with_width.append(&legend.clone().to_xdr(&env));
+    colors_extended_with_width.extend_from_slice(&4u32.to_be_bytes());
+
+    let tiled_index = client.glyph_mint_begin(
+        &user_2,
+        &user_2,
+        &user_2,
+        &legend,
+        &4,
+        &1,
+        &env.crypto().sha256(&colors_extended_with_width).to_bytes(),
+        &title,
+        &story,
+        &None,
+    );
+
+    let res = client.try_glyph_mint_quote(&GlyphMintKind::Tiled(tiled_index));
+
+    assert_eq!(res, Err(Ok(Error::GlyphTilesMissing)));
+
+    client.glyph_mint_tile(&tiled_index, &0, &tile_colors);
+
+    let quote = fund(GlyphMintKind::Tiled(tiled_index));
+
+    assert_eq!(quote.color_fee, color_fee);
+
+    client.glyph_mint_finalize(&tiled_index);
+
+    assert_eq!(fee_sac_client.balance(&user_2), 0);
+}
+
+#[test]
+fn test_sale_quote() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        client,
+        fee_sac_address,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let amount = 100_0000001;
+
+    let user_1 = Address::generate(&env);
+    let user_2 = Address::generate(&env);
+    let collaborator = Address::generate(&env);
+    let remainder = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&user_1, &(glyph_fee + mine_fee));
+    fee_sac_admin_client.mint(&user_2, &amount);
+
+    client.color_royalty_max_colors_set(&8);
+    client.color_royalty_remainder_set(&remainder);
+
+    client.color_claim(&user_1, &user_1, &0);
+
+    let glyph_1_hash = mint_256_colors(&env, &client, &user_1);
+
+    client.glyph_author_split_set(
+        &glyph_1_hash,
+        &Some(RoyaltySplit {
+            recipients: vec![&env, (user_1.clone(), 3_333), (collaborator.clone(), 6_667)],
+            immutable: false,
+        }),
+    );
+
+    let quote = client.sale_quote(&glyph_1_hash, &fee_sac_address, &amount);
+
+    // ensure the quote accounts for every unit of the sale
+    let author_amount: i128 = quote.author_amounts.iter().map(|(_, amount)| amount).sum();
+    let color_amount: i128 = quote.color_amounts.iter().map(|(.., amount)| amount).sum();
+
+    assert_eq!(
+        author_amount + quote.color_owner_amount + quote.seller_amount,
+        amount
+    );
+    assert_eq!(color_amount + quote.remainder_fee, quote.color_owner_amount);
+    assert_eq!(quote.remainder_address, Some(remainder.clone()));
+    assert_eq!(quote.color_amounts.len(), 8);
+    assert_eq!(quote.color_amounts.get(0).unwrap().2, Some(user_1.clone()));
+    assert_eq!(quote.color_amounts.get(1).unwrap().2, None);
+    assert_eq!(quote.color_amounts.get(1).unwrap().3, 0);
+
+    // ensure the quote didn't write anything
+    assert_eq!(client.royalties_get(&user_1, &fee_sac_address), 0);
+
+    // ensure settlement pays exactly what was quoted
+    client.offer_sell_glyph(
+        &glyph_1_hash,
+        &OfferBuy::Asset(fee_sac_address.clone(), amount),
+    );
+    client.offer_sell_asset(
+        &OfferSellAsset(user_2.clone(), fee_sac_address.clone(), amount),
+        &glyph_1_hash,
+    );
+
+    client.color_royalties_collect(&0, &vec![&env, glyph_1_hash], &fee_sac_address);
+
+    let (_, user_1_author_amount) = quote.author_amounts.get(0).unwrap();
+    let (_, collaborator_amount) = quote.author_amounts.get(1).unwrap();
+
+    assert_eq!(
+        client.royalties_get(&user_1, &fee_sac_address),
+        user_1_author_amount + quote.seller_amount + quote.color_amounts.get(0).unwrap().3
+    );
+    assert_eq!(
+        client.royalties_get(&collaborator, &fee_sac_address),
+        collaborator_amount
+    );
+    assert_eq!(
+        client.royalties_get(&remainder, &fee_sac_address),
+        quote.remainder_fee
+    );
+}
+
+#[test]
+fn test_glyph_transparency() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        client,
+        fee_sac_address,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let amount = 100_0000000;
+
+    let user_1 = Address::generate(&env);
+    let user_2 = Address::generate(&env);
+    let color_owner = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&user_1, &(glyph_fee + mine_fee * 2));
+    fee_sac_admin_client.mint(&user_2, &amount);
+
+    // ensure the transparent color can't be owned
+    let res = client.try_color_claim(&user_1, &user_1, &TRANSPARENT_COLOR);
+
+    assert_eq!(res, Err(Ok(Error::ColorOutOfRange)));
+
+    client.color_claim(&user_1, &color_owner, &0);
+    client.color_claim(&user_1, &color_owner, &255);
+
+    let title = String::from_str(&env, "Hello World");
+    let story = String::from_str(&env, "Lorem Ipsum");
+
+    // ensure a glyph needs at least one visible pixel
+    let res = client.try_glyph_mint(
+        &user_1,
+        &user_1,
+        &user_1,
+        &Bytes::from_array(&env, &[0, 0, 0, 0]),
+        &vec![&env, TRANSPARENT_COLOR],
+        &2,
+        &title,
+        &story,
+        &None,
+    );
+
+    assert_eq!(res, Err(Ok(Error::GlyphEmpty)));
+
+    // 1 black, 1 blue and 2 transparent pixels
+    let glyph_1_hash = client.glyph_mint(
+        &user_1,
+        &user_1,
+        &user_1,
+        &Bytes::from_array(&env, &[0, 2, 2, 1]),
+        &vec![&env, 0, 255, TRANSPARENT_COLOR],
+        &2,
+        &title,
+        &story,
+        &None,
+    );
+
+    // ensure transparent pixels are left out of the royalty palette
+    let glyph_palette = client.glyph_palette_get(&glyph_1_hash);
+
+    assert_eq!(glyph_palette.pixels, 2);
+    assert_eq!(glyph_palette.counts, vec![&env, (0, 1), (255, 1)]);
+
+    client.offer_sell_glyph(
+        &glyph_1_hash,
+        &OfferBuy::Asset(fee_sac_address.clone(), amount),
+    );
+    client.offer_sell_asset(
+        &OfferSellAsset(user_2.clone(), fee_sac_address.clone(), amount),
+        &glyph_1_hash,
+    );
+
+    client.color_royalties_collect(&0, &vec![&env, glyph_1_hash], &fee_sac_address);
+    client.color_royalties_collect(&255, &vec![&env, glyph_1_hash], &fee_sac_address);
+
+    // ensure the visible colors split the whole color owner royalty
+    assert_eq!(
+        client.royalties_get(&color_owner, &fee_sac_address),
+        2_0000000
+    );
+}
+
+#[test]
+fn test_glyph_lineage_royalties() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        client,
+        fee_sac_address,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let amount = 100_0000000;
+
+    let author_a = Address::generate(&env);
+    let author_b = Address::generate(&env);
+    let author_c = Address::generate(&env);
+    let author_d = Address::generate(&env);
+    let buyer = Address::generate(&env);
+
+    for author in [&author_a, &author_b, &author_c, &author_d] {
+        fee_sac_admin_client.mint(author, &(glyph_fee * 2));
+    }
+
+    fee_sac_admin_client.mint(&buyer, &amount);
+
+    // half of each author amount cascades up to two levels of parents
+    client.glyph_lineage_set(&50, &2);
+
+    let mint_glyph = |author: &Address, colors: [u8; 4], parents: Option<Vec<u32>>| {
+        client.try_glyph_mint(
+            author,
+            author,
+            author,
+            &Bytes::from_array(&env, &colors),
+            &vec![&env, 0, 16777215],
+            &2,
+            &String::from_str(&env, "Hello World"),
+            &String::from_str(&env, "Lorem Ipsum"),
+            &Some(GlyphMintOptions {
+                author_split: None,
+                author_split_immutable: false,
+                author_royalty_rate: None,
+                parents,
+                max_supply: None,
+            }),
+        )
+    };
+
+    let glyph_a = mint_glyph(&author_a, [0, 1, 1, 0], None).unwrap().unwrap();
+
+    let res = mint_glyph(&author_b, [1, 0, 0, 1], Some(vec![&env, glyph_a, glyph_a]));
+
+    assert_eq!(res, Err(Ok(Error::GlyphParentsInvalid)));
+
+    let res = mint_glyph(&author_b, [1, 0, 0, 1], Some(vec![&env, glyph_a + 1]));
+
+    assert_eq!(res, Err(Ok(Error::GlyphNotMinted)));
+
+    let glyph_b = mint_glyph(&author_b, [1, 0, 0, 1], Some(vec![&env, glyph_a]))
+        .unwrap()
+        .unwrap();
+    let glyph_c = mint_glyph(&author_c, [0, 0, 1, 1], Some(vec![&env, glyph_b]))
+        .unwrap()
+        .unwrap();
+    let glyph_d = mint_glyph(&author_d, [1, 1, 0, 0], Some(vec![&env, glyph_c]))
+        .unwrap()
+        .unwrap();
+
+    // ensure the cascade can't be set deeper than a sale can afford
+    assert_eq!(
+        client.try_glyph_lineage_set(&50, &4),
+        Err(Ok(Error::GlyphLineageTooDeep))
+    );
+
+    // ensure the lineage is bounded by the max depth
+    assert_eq!(
+        client.glyph_lineage_get(&glyph_d),
+        (vec![&env, glyph_c, glyph_b], 0)
+    );
+    assert_eq!(client.glyph_lineage_get(&glyph_a), (vec![&env], 1));
+    assert_eq!(
+        client.glyph_children_get(&glyph_a, &0, &10),
+        vec![&env, glyph_b]
+    );
+
+    let quote = client.sale_quote(&glyph_c, &fee_sac_address, &amount);
+
+    assert_eq!(
+        quote.author_amounts,
+        vec![
+            &env,
+            (author_a.clone(), 1_2500000),
+            (author_b.clone(), 1_2500000),
+            (author_c.clone(), 2_5000000),
+        ]
+    );
+
+    // ensure the cascade stops at the max depth with the last ancestor keeping its slice
+    let quote = client.sale_quote(&glyph_d, &fee_sac_address, &amount);
+
+    assert_eq!(
+        quote.author_amounts,
+        vec![
+            &env,
+            (author_b.clone(), 1_2500000),
+            (author_c.clone(), 1_2500000),
+            (author_d.clone(), 2_5000000),
+        ]
+    );
+
+    // ensure an author paid twice in one lineage is paid once with both slices
+    let glyph_e = mint_glyph(&author_b, [1, 1, 1, 0], Some(vec![&env, glyph_c]))
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(
+        client
+            .sale_quote(&glyph_e, &fee_sac_address, &amount)
+            .author_amounts,
+        vec![
+            &env,
+            (author_b.clone(), 1_2500000 + 2_5000000),
+            (author_c.clone(), 1_2500000),
+        ]
+    );
+
+    client.offer_sell_glyph(&glyph_d, &OfferBuy::Asset(fee_sac_address.clone(), amount));
+    client.offer_sell_asset(
+        &OfferSellAsset(buyer.clone(), fee_sac_address.clone(), amount),
+        &glyph_d,
+    );
+
+    assert_eq!(client.royalties_get(&author_a, &fee_sac_address), 0);
+    assert_eq!(client.royalties_get(&author_b, &fee_sac_address), 1_2500000);
+    assert_eq!(client.royalties_get(&author_c, &fee_sac_address), 1_2500000);
+    assert_eq!(
+        client.royalties_get(&author_d, &fee_sac_address),
+        2_5000000 + quote.seller_amount
+    );
+}
+
+#[test]
+fn test_glyph_mint_recolor() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        client,
+        fee_sac_address,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let amount = 100_0000000;
+
+    let author = Address::generate(&env);
+    let recolorist = Address::generate(&env);
+    let color_owner = Address::generate(&env);
+    let buyer = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&author, &glyph_fee);
+    fee_sac_admin_client.mint(&recolorist, &(glyph_fee * 3));
+    fee_sac_admin_client.mint(&color_owner, &mine_fee);
+    fee_sac_admin_client.mint(&buyer, &amount);
+
+    client.color_claim(&color_owner, &color_owner, &255);
+
+    let colors = Bytes::from_array(&env, &[0, 1, 1, 1]);
+    let title = String::from_str(&env, "Hello World");
+    let story = String::from_str(&env, "Lorem Ipsum");
+
+    let glyph_1_hash = client.glyph_mint(
+        &author,
+        &author,
+        &author,
+        &colors,
+        &vec![&env, 0, 16777215],
+        &2,
+        &title,
+        &story,
+        &None,
+    );
+
+    let recolor = |parent: &u32, legend: Vec<u32>| {
+        client.try_glyph_mint_recolor(
+            &recolorist,
+            &recolorist,
+            &recolorist,
+            parent,
+            &legend,
+            &title,
+            &story,
+            &None,
+        )
+    };
+
+    // ensure recolors pay the original author before the admin sets a rate, and take a split
+    // like any other mint
+    let author_split = vec![&env, (recolorist.clone(), 5_000), (author.clone(), 5_000)];
+    let default_rate_recolor = client.glyph_mint_recolor(
+        &recolorist,
+        &recolorist,
+        &recolorist,
+        &glyph_1_hash,
+        &vec![&env, 255, 0],
+        &title,
+        &story,
+        &Some(GlyphMintOptions {
+            author_split: Some(author_split.clone()),
+            ..Default::default()
+        }),
+    );
+
+    assert_eq!(
+        client.glyph_author_split_get(&default_rate_recolor),
+        Some(RoyaltySplit {
+            recipients: author_split,
+            immutable: false,
+        })
+    );
+
+    env.as_contract(&client.address, || {
+        let glyph_lineage = env
+            .storage()
+            .persistent()
+            .get::<Storage, GlyphLineage>(&Storage::GlyphLineage(default_rate_recolor))
+            .unwrap();
+
+        assert_eq!(glyph_lineage.royalty_rate, 10);
+    });
+
+    // a fifth of the recolor's author amount goes to the original author
+    client.glyph_recolor_royalty_rate_set(&20);
+
+    // ensure the legend must cover the same indexes without repeats
+    assert_eq!(
+        recolor(&glyph_1_hash, vec![&env, 255]),
+        Err(Ok(Error::RecolorParentInvalid))
+    );
+    assert_eq!(
+        recolor(&glyph_1_hash, vec![&env, 255, 255]),
+        Err(Ok(Error::LegendDuplicate))
+    );
+    assert_eq!(
+        recolor(&glyph_1_hash, vec![&env, 0, 16777215]),
+        Err(Ok(Error::GlyphAlreadyMinted))
+    );
+
+    // ensure a recolor's lineage comes from its parent alone
+    assert_eq!(
+        client.try_glyph_mint_recolor(
+            &recolorist,
+            &recolorist,
+            &recolorist,
+            &glyph_1_hash,
+            &vec![&env, 16777215, 255],
+            &title,
+            &story,
+            &Some(GlyphMintOptions {
+                parents: Some(vec![&env, glyph_1_hash]),
+                ..Default::default()
+            })
+        ),
+        Err(Ok(Error::GlyphParentsInvalid))
+    );
+
+    // an unsorted legend is fine since the indexes come from the parent
+    let glyph_2_hash = recolor(&glyph_1_hash, vec![&env, 16777215, 255])
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(
+        recolor(&glyph_2_hash, vec![&env, 16777215, 255]),
+        Err(Ok(Error::GlyphAlreadyMinted))
+    );
+
+    // ensure the rendered image is deduplicated against plain glyphs too
+    assert_eq!(
+        client.try_glyph_mint(
+            &author,
+            &author,
+            &author,
+            &Bytes::from_array(&env, &[1, 0, 0, 0]),
+            &vec![&env, 255, 16777215],
+            &2,
+            &title,
+            &story,
+            &None
+        ),
+        Err(Ok(Error::GlyphAlreadyMinted))
+    );
+
+    // ensure only the legend is stored and the colors resolve through the parent
+    let glyph = client.glyph_get(&glyph_2_hash);
+
+    assert_eq!(glyph.colors, colors);
+    assert_eq!(glyph.legend, vec![&env, 16777215, 255]);
+    assert_eq!(client.glyph_lineage_get(&glyph_1_hash), (vec![&env], 2));
+    assert_eq!(
+        client.glyph_children_get(&glyph_1_hash, &0, &10),
+        vec![&env, default_rate_recolor, glyph_2_hash]
+    );
+
+    // ensure the new legend's color owners earn by the parent's pixel counts
+    assert_eq!(
+        client.glyph_palette_get(&glyph_2_hash).counts,
+        vec![&env, (255, 3), (16777215, 1)]
+    );
+
+    client.offer_sell_glyph(
+        &glyph_2_hash,
+        &OfferBuy::Asset(fee_sac_address.clone(), amount),
+    );
+    client.offer_sell_asset(
+        &OfferSellAsset(buyer.clone(), fee_sac_address.clone(), amount),
+        &glyph_2_hash,
+    );
+
+    client.color_royalties_collect(&255, &vec![&env, glyph_2_hash], &fee_sac_address);
+
+    assert_eq!(client.royalties_get(&author, &fee_sac_address), 1_0000000);
+    // the unclaimed white pixel's share goes back to the recolorist as the seller
+    assert_eq!(
+        client.royalties_get(&recolorist, &fee_sac_address),
+        amount - 1_0000000 - 1_5000000
+    );
+    assert_eq!(
+        client.royalties_get(&color_owner, &fee_sac_address),
+        1_5000000
+    );
+}
+
+#[test]
+fn test_glyph_mint_composite() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let mine_fee = 250_0000000;
+    let glyph_fee = 1_0000000;
+    let color_owner_royalty_rate = 2;
+    let glyph_author_royalty_rate = 5;
+
+    let Init {
+        client,
+        fee_sac_address,
+        fee_sac_admin_client,
+        ..
+    } = initialize(
+        &env,
+        mine_fee,
+        glyph_fee,
+        color_owner_royalty_rate,
+        glyph_author_royalty_rate,
+    );
+
+    let amount = 100_0000000;
+
+    let author_a = Address::generate(&env);
+    let author_b = Address::generate(&env);
+    let composer = Address::generate(&env);
+    let buyer = Address::generate(&env);
+
+    fee_sac_admin_client.mint(&author_a, &glyph_fee);
+    fee_sac_admin_client.mint(&author_b, &glyph_fee);
+    fee_sac_admin_client.mint(&composer, &(glyph_fee * 6));
+    fee_sac_admin_client.mint(&buyer, &amount);
+
+    // half of the composite's author amount goes to the layer authors
+    client.glyph_composite_royalty_rate_set(&50);
+
+    let mint_glyph = |author: &Address, colors: [u8; 4], legend: Vec<u32>| {
+        client.glyph_mint(
+            author,
+            author,
+            author,
+            &Bytes::from_array(&env, &colors),
+            &legend,
+            &2,
+            &String::from_str(&env, "Hello World"),
+            &String::from_str(&env, "Lorem Ipsum"),
+            &None,
+        )
+    };
+
+    // a 2x2 red block and a 2x2 blue diagonal with transparent corners
+    let glyph_a = mint_glyph(&author_a, [0, 0, 0, 0], vec![&env, 0xFF0000]);
+    let glyph_b = mint_glyph(
+        &author_b,
+        [0, 1, 1, 0],
+        vec![&env, 0x0000FF, TRANSPARENT_COLOR],
+    );
+
+    let mint_composite = |source: &Address, layers: Vec<(u32, u32, u32, u32)>, width: u32| {
+        client.try_glyph_mint_composite(
+            source,
+            source,
+            source,
+            &layers,
+            &width,
+            &2,
+            &String::from_str(&env, "Hello World"),
+            &String::from_str(&env, "Lorem Ipsum"),
+            &None,
+        )
+    };
+
+    // ensure the source owns or authored every layer
+    assert_eq!(
+        mint_composite(&composer, vec![&env, (glyph_a, 0, 0, 0)], 2),
+        Err(Ok(Error::CompositeLayerNotOwned))
+    );
+
+    // an approved spender can layer a glyph it doesn't own
+    client.approve(&author_a, &composer, &glyph_a, &200);
+    client.glyph_owner_transfer(&glyph_b, &composer);
+
+    assert_eq!(
+        mint_composite(&composer, vec![&env], 2),
+        Err(Ok(Error::CompositeLayersInvalid))
+    );
+    assert_eq!(
+        mint_composite(&composer, vec![&env, (glyph_a, 2, 0, 0)], 2),
+        Err(Ok(Error::CompositeLayersInvalid))
+    );
+
+    // a lone layer covering the canvas renders the same image as its source
+    assert_eq!(
+        mint_composite(&composer, vec![&env, (glyph_a, 0, 0, 0)], 2),
+        Err(Ok(Error::GlyphAlreadyMinted))
+    );
+
+    // ensure the distinct layer glyphs are capped like any other lineage
+    let glyph_c = mint_glyph(&composer, [0, 0, 0, 0], vec![&env, 0x00FF00]);
+    let glyph_d = mint_glyph(&composer, [0, 0, 0, 0], vec![&env, 0x00FF01]);
+    let glyph_e = mint_glyph(&composer, [0, 0, 0, 0], vec![&env, 0x00FF02]);
+
+    assert_eq!(
+        mint_composite(
+            &composer,
+            vec![
+                &env,
+                (glyph_a, 0, 0, 0),
+                (glyph_b, 0, 0, 1),
+                (glyph_c, 0, 0, 2),
+                (glyph_d, 0, 0, 3),
+                (glyph_e, 0, 0, 4),
+            ],
+            2,
+        ),
+        Err(Ok(Error::CompositeLayersInvalid))
+    );
+
+    // the diagonal drawn over the block, given out of z order
+    let composite = mint_composite(
+        &composer,
+        vec![&env, (glyph_b, 0, 0, 1), (glyph_a, 0, 0, 0)],
+        2,
+    )
+    .unwrap()
+    .unwrap();
+
+    let glyph = client.glyph_get(&composite);
+
+    assert_eq!(glyph.colors, Bytes::from_array(&env, &[0, 1, 1, 0]));
+    assert_eq!(glyph.legend, vec![&env, 0x0000FF, 0xFF0000]);
+    assert_eq!(
+        client.glyph_layers_get(&composite),
+        vec![&env, (glyph_a, 0, 0, 0), (glyph_b, 0, 0, 1)]
+    );
+    assert_eq!(client.glyph_layers_get(&glyph_a), vec![&env]);
+
+    // the block over the diagonal hides it, and layers are clipped to the canvas
+    let covered = mint_composite(
+        &composer,
+        vec![&env, (glyph_b, 0, 0, 0), (glyph_a, 1, 0, 1)],
+        3,
+    )
+    .unwrap()
+    .unwrap();
+
+    let glyph = client.glyph_get(&covered);
+
+    assert_eq!(glyph.colors, Bytes::from_array(&env, &[0, 1, 1, 2, 1, 1]));
+    assert_eq!(
+        glyph.legend,
+        vec![&env, 0x0000FF, 0xFF0000, TRANSPARENT_COLOR]
+    );
+    assert_eq!(
+        client.glyph_palette_get(&covered).counts,
+        vec![&env, (0xFF0000, 4), (0x0000FF, 1)]
+    );
+    assert_eq!(client.glyph_lineage_get(&glyph_a), (vec![&env], 2));
+    assert_eq!(
+        client.glyph_children_get(&glyph_a, &1, &10),
+        vec![&env, covered]
+    );
+
+    // the layer authors split half of the author amount
+    client.offer_sell_glyph(
+        &composite,
+        &OfferBuy::Asset(fee_sac_address.clone(), amount),
+    );
+    client.offer_sell_asset(
+        &OfferSellAsset(buyer.clone(), fee_sac_address.clone(), amount),
+        &composite,
+    );
+
+    assert_eq!(client.royalties_get(&author_a, &fee_sac_address), 1_2500000);
+    assert_eq!(client.royalties_get(&author_b, &fee_sac_address), 1_2500000);
+    assert_eq!(
+        client.royalties_get(&composer, &fee_sac_address),
+        amount - 2_5000000
+    );
+}
@@ -2,6 +2,10 @@ use soroban_sdk::{testutils::Address as _, token, vec, Address, Bytes, Env, Stri
 
 use crate::{Contract, ContractArgs, ContractClient};
 
+// mainnet's per transaction limits on written and on all accessed ledger entries
+pub const TX_MAX_WRITE_ENTRIES: u32 = 50;
+pub const TX_MAX_LEDGER_ENTRIES: u32 = 100;
+
 #[allow(dead_code)]
 pub struct Init<'a> {
     pub admin: Address,
@@ -88,6 +92,7 @@ pub fn mint(
         &45,
         &String::from_str(env, "Hello World"),
         &String::from_str(env, "Lorem Ipsum"),
+        &None,
     );
 
     glyph_index